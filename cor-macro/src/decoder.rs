@@ -10,29 +10,73 @@ pub fn expand(input: &DeriveInput) -> TokenStream {
         ..
     } = input;
 
-    let body = quote(|t| match data {
-        Data::Struct(DataStruct { fields, .. }) => {
-            for field in fields {
-                match crate::utils::get_key(field) {
-                    Some(key) => {
-                        let key_name = &field.ident;
-                        quote!(t, { #key_name: e.get_and_convert(#key)?, });
-                    }
-                    None => {
-                        let key_name = &field.ident;
-                        quote!(t, { #key_name: ::std::default::Default::default(), });
-                    }
+    let fields: Vec<_> = match data {
+        Data::Struct(DataStruct { fields, .. }) => fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let slot = Ident::new(&format!("__field{i}"), Span::call_site());
+                (field, crate::utils::get_key(field), slot)
+            })
+            .collect(),
+        Data::Enum(_) => vec![],
+        Data::Union(_) => todo!(),
+    };
+
+    let body = quote(|t| {
+        for (field, key, _) in &fields {
+            let key_name = &field.ident;
+            match key {
+                Some(key) => {
+                    quote!(t, { #key_name: e.get_and_convert(#key)?, });
+                }
+                None => {
+                    quote!(t, { #key_name: ::std::default::Default::default(), });
+                }
+            }
+        }
+    });
+
+    // Each declared field is decoded straight from the parser as it's read, and
+    // nested derived types decode their own fields the same way, so neither a
+    // `Value` nor an `Entries` is built. Unknown keys are skipped.
+    let slots = quote(|t| {
+        for (field, _, slot) in fields.iter().filter(|(_, key, _)| key.is_some()) {
+            let ty = &field.ty;
+            quote!(t, {
+                let mut #slot: ::std::option::Option<#ty> = ::std::option::Option::None;
+            });
+        }
+    });
+
+    let arms = quote(|t| {
+        for (_, key, slot) in &fields {
+            if let Some(key) = key {
+                quote!(t, {
+                    #key => p.parse_field(key, ty, &mut #slot)?,
+                });
+            }
+        }
+    });
+
+    let init = quote(|t| {
+        for (field, key, slot) in &fields {
+            let key_name = &field.ident;
+            match key {
+                Some(key) => {
+                    quote!(t, { #key_name: p.take_field(#key, #slot)?, });
+                }
+                None => {
+                    quote!(t, { #key_name: ::std::default::Default::default(), });
                 }
             }
         }
-        Data::Enum(_) => {}
-        Data::Union(_) => todo!(),
     });
 
     let (_, ty_generics, where_clause) = generics.split_for_impl();
 
-    // Add a bound `T: Decode<'de>` to every type parameter of `T`.
-    let bound: TypeParamBound = parse_quote!(::cor::Decode<'decode>);
+    // Add a bound `T: FieldDecoder<'de>` to every type parameter of `T`.
+    let bound: TypeParamBound = parse_quote!(::cor::FieldDecoder<'decode>);
     let mut params = generics.params.clone();
     let mut lifetime = LifetimeParam::new(Lifetime::new("'decode", Span::call_site()));
 
@@ -50,6 +94,28 @@ pub fn expand(input: &DeriveInput) -> TokenStream {
             fn decode(e: &::cor::Entries<'decode>) -> ::cor::Result<Self> {
                 Ok(Self { #body })
             }
+
+            fn parse_fields<'p: 'decode, R: ::cor::__private::Source<'p>>(
+                p: &mut ::cor::__private::Parser<'_, 'p, R>,
+            ) -> ::cor::Result<Self> {
+                #slots
+                while let ::std::option::Option::Some((key, ty)) = p.next_field()? {
+                    match key {
+                        #arms
+                        _ => p.skip_unknown_field(key, ty)?,
+                    }
+                }
+                Ok(Self { #init })
+            }
+        }
+
+        impl <#lifetime, #params> ::cor::FieldDecoder<'decode> for #ident #ty_generics #where_clause {
+            fn decode_field<'p: 'decode, R: ::cor::__private::Source<'p>>(
+                p: &mut ::cor::__private::Parser<'_, 'p, R>,
+                ty: u8,
+            ) -> ::cor::Result<Self> {
+                p.decode_struct(ty, |p| <Self as ::cor::Decoder<'decode>>::parse_fields(p))
+            }
         }
     });
     t
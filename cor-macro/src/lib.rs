@@ -1,6 +1,7 @@
 mod decoder;
 mod encoder;
 mod utils;
+mod value;
 
 use proc_macro::TokenStream;
 
@@ -9,7 +10,24 @@ pub fn encoder(input: TokenStream) -> TokenStream {
     encoder::expand(&syn::parse_macro_input!(input)).into()
 }
 
-#[proc_macro_derive(Decoder)]
+#[proc_macro_derive(Decoder, attributes(key))]
 pub fn decoder(input: TokenStream) -> TokenStream {
     decoder::expand(&syn::parse_macro_input!(input)).into()
 }
+
+/// Builds a `Value` from the text format notation:
+/// `42` is an `Int`, `42u` a `UInt`, `1.5` an `F64` and `1.5f` an `F32`;
+/// `(1 2 3)` is `Bytes`, `#7(1 2)` an extension value, `[..]` a list (`[]str`
+/// when empty) and `{ key: value, .. }` a struct. Fields may also be written without braces:
+/// `value!{ 1: "name", 2: 42u, 3: [1.0, 2.0], 4: { 1: true } }`.
+/// A key may appear only once in each struct.
+#[proc_macro]
+pub fn value(input: TokenStream) -> TokenStream {
+    value::expand(&syn::parse_macro_input!(input)).into()
+}
+
+/// Like `value!`, but builds the `Entries` of a struct from its fields.
+#[proc_macro]
+pub fn entries(input: TokenStream) -> TokenStream {
+    value::expand_entries(&syn::parse_macro_input!(input)).into()
+}
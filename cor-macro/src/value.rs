@@ -0,0 +1,324 @@
+use proc_macro2::{Literal, Span, TokenStream};
+use quote2::{Quote, quote};
+use std::collections::HashSet;
+use syn::{
+    ext::IdentExt,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    *,
+};
+
+/// Input of `value!`: a single value, or the fields of a struct written
+/// without the surrounding braces.
+pub enum Input {
+    Value(Node),
+    Fields(Fields),
+}
+
+pub struct Fields(Vec<(Literal, Node)>);
+
+pub struct Node {
+    kind: Kind,
+    span: Span,
+    tokens: Tokens,
+}
+
+enum Tokens {
+    /// Numbers and booleans, already suffixed with their Rust type.
+    Scalar(TokenStream),
+    Str(LitStr),
+    Bytes(Vec<Literal>),
+    /// Element kind and elements.
+    List(Kind, Vec<Node>),
+    Struct(Fields),
+    Ext(Literal, Vec<Literal>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Bool,
+    F32,
+    F64,
+    Int,
+    UInt,
+    Str,
+    Bytes,
+    List,
+    Struct,
+    Ext,
+}
+
+impl Kind {
+    fn variant(self) -> Ident {
+        let name = match self {
+            Kind::Bool => "Bool",
+            Kind::F32 => "F32",
+            Kind::F64 => "F64",
+            Kind::Int => "Int",
+            Kind::UInt => "UInt",
+            Kind::Str => "Str",
+            Kind::Bytes => "Bytes",
+            Kind::List => "List",
+            Kind::Struct => "Struct",
+            Kind::Ext => "Ext",
+        };
+        Ident::new(name, Span::call_site())
+    }
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() || (input.peek(LitInt) && input.peek2(Token![:])) {
+            return input.parse().map(Input::Fields);
+        }
+        let node = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after value"));
+        }
+        Ok(Input::Value(node))
+    }
+}
+
+impl Parse for Fields {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut fields = Vec::new();
+        let mut keys = HashSet::new();
+        while !input.is_empty() {
+            let key: LitInt = input.parse()?;
+            if !key.suffix().is_empty() {
+                return Err(Error::new(key.span(), "keys are written without a suffix"));
+            }
+            let id: u16 = key.base10_parse()?;
+            if !keys.insert(id) {
+                return Err(Error::new(key.span(), format!("duplicate key `{id}`")));
+            }
+            let mut key_lit = Literal::u16_suffixed(id);
+            key_lit.set_span(key.span());
+            input.parse::<Token![:]>()?;
+            fields.push((key_lit, input.parse()?));
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Fields(fields))
+    }
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let span = input.span();
+        let node = |kind, tokens| Node { kind, span, tokens };
+
+        if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            return Ok(node(Kind::Struct, Tokens::Struct(content.parse()?)));
+        }
+        if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            let items = Punctuated::<Node, Token![,]>::parse_terminated(&content)?;
+            if items.is_empty() && input.peek(Ident::peek_any) {
+                return empty_list(span, Ident::parse_any(input)?);
+            }
+            return list(span, items.into_iter().collect());
+        }
+        if input.peek(token::Paren) {
+            return Ok(node(Kind::Bytes, Tokens::Bytes(bytes(input)?)));
+        }
+        if input.peek(Token![#]) {
+            input.parse::<Token![#]>()?;
+            let tag: LitInt = input.parse()?;
+            let tag = Literal::u64_suffixed(tag.base10_parse()?);
+            return Ok(node(Kind::Ext, Tokens::Ext(tag, bytes(input)?)));
+        }
+
+        let minus: Option<Token![-]> = input.parse()?;
+        let lit: Lit = input.parse()?;
+        let (kind, ty, digits) = match &lit {
+            Lit::Bool(lit) if minus.is_none() => {
+                let mut t = TokenStream::new();
+                quote!(t, { #lit });
+                return Ok(node(Kind::Bool, Tokens::Scalar(t)));
+            }
+            Lit::Str(lit) if minus.is_none() => {
+                return Ok(node(Kind::Str, Tokens::Str(lit.clone())));
+            }
+            Lit::ByteStr(lit) if minus.is_none() => {
+                let bytes = lit.value().into_iter().map(Literal::u8_suffixed);
+                return Ok(node(Kind::Bytes, Tokens::Bytes(bytes.collect())));
+            }
+            Lit::Int(lit) => match lit.suffix() {
+                "" => (Kind::Int, "i64", lit.base10_digits()),
+                "u" if minus.is_none() => (Kind::UInt, "u64", lit.base10_digits()),
+                "u" => {
+                    let msg = "an unsigned literal cannot be negative";
+                    return Err(Error::new(lit.span(), msg));
+                }
+                "f" => (Kind::F32, "f32", lit.base10_digits()),
+                _ => return Err(Error::new(lit.span(), "expected no suffix, `u` or `f`")),
+            },
+            Lit::Float(lit) => match lit.suffix() {
+                "" => (Kind::F64, "f64", lit.base10_digits()),
+                "f" => (Kind::F32, "f32", lit.base10_digits()),
+                _ => return Err(Error::new(lit.span(), "expected no suffix or `f`")),
+            },
+            lit => return Err(Error::new(lit.span(), "unsupported value")),
+        };
+
+        // `1f` isn't valid Rust, so floats get a fraction before their suffix.
+        let dot = match kind {
+            Kind::F32 if !digits.contains(['.', 'e', 'E']) => ".0",
+            _ => "",
+        };
+        let mut number: Literal = format!("{digits}{dot}{ty}").parse().unwrap();
+        number.set_span(lit.span());
+
+        let mut t = TokenStream::new();
+        quote!(t, { #minus #number });
+        Ok(node(kind, Tokens::Scalar(t)))
+    }
+}
+
+/// Parses `(1 2 3)`, the way `Debug` prints bytes.
+fn bytes(input: ParseStream) -> Result<Vec<Literal>> {
+    let content;
+    parenthesized!(content in input);
+    let mut bytes = Vec::new();
+    while !content.is_empty() {
+        let byte: LitInt = content.parse()?;
+        bytes.push(Literal::u8_suffixed(byte.base10_parse()?));
+    }
+    Ok(bytes)
+}
+
+fn list(span: Span, items: Vec<Node>) -> Result<Node> {
+    let Some(first) = items.first() else {
+        return Err(Error::new(
+            span,
+            "an empty list needs its element type, e.g. `[]str`",
+        ));
+    };
+    if first.kind == Kind::Ext {
+        return Err(Error::new(
+            first.span,
+            "lists of extension values aren't supported",
+        ));
+    }
+    if let Some(item) = items.iter().find(|item| item.kind != first.kind) {
+        return Err(Error::new(
+            item.span,
+            "list elements must all have the same type",
+        ));
+    }
+    Ok(Node {
+        kind: Kind::List,
+        span,
+        tokens: Tokens::List(first.kind, items),
+    })
+}
+
+/// Parses the element type in `[]str`, the way the text format writes empty lists.
+fn empty_list(span: Span, ty: Ident) -> Result<Node> {
+    let kind = match ty.to_string().as_str() {
+        "bool" => Kind::Bool,
+        "f32" => Kind::F32,
+        "f64" => Kind::F64,
+        "int" => Kind::Int,
+        "uint" => Kind::UInt,
+        "str" => Kind::Str,
+        "bytes" => Kind::Bytes,
+        "list" => Kind::List,
+        "struct" => Kind::Struct,
+        _ => return Err(Error::new(ty.span(), "unknown list element type")),
+    };
+    Ok(Node {
+        kind: Kind::List,
+        span,
+        tokens: Tokens::List(kind, Vec::new()),
+    })
+}
+
+pub fn expand(input: &Input) -> TokenStream {
+    let mut t = TokenStream::new();
+    match input {
+        Input::Value(node) => value(&mut t, node),
+        Input::Fields(fields) => {
+            let entries = quote(|t| entries(t, fields));
+            quote!(t, { ::cor::Value::Struct(#entries) });
+        }
+    }
+    t
+}
+
+pub fn expand_entries(fields: &Fields) -> TokenStream {
+    let mut t = TokenStream::new();
+    entries(&mut t, fields);
+    t
+}
+
+fn entries(t: &mut TokenStream, Fields(fields): &Fields) {
+    let inserts = quote(|t| {
+        for (key, node) in fields {
+            let value = quote(|t| value(t, node));
+            quote!(t, { entries.insert(#key, #value); });
+        }
+    });
+    quote!(t, {{
+        let mut entries = ::cor::Entries::new();
+        #inserts
+        entries
+    }});
+}
+
+fn value(t: &mut TokenStream, node: &Node) {
+    match &node.tokens {
+        Tokens::Ext(tag, data) => {
+            let data = quote(|t| byte_slice(t, data));
+            quote!(t, {
+                ::cor::Value::Ext { tag: #tag, data: #data }
+            });
+        }
+        _ => {
+            let variant = node.kind.variant();
+            let item = quote(|t| item(t, node));
+            quote!(t, { ::cor::Value::#variant(#item) });
+        }
+    }
+}
+
+/// The payload of a `Value` variant, which is also how the element is stored
+/// in the `Vec` of the matching `List` variant.
+fn item(t: &mut TokenStream, node: &Node) {
+    match &node.tokens {
+        Tokens::Scalar(tokens) => {
+            quote!(t, { #tokens });
+        }
+        Tokens::Str(lit) => {
+            quote!(t, { ::std::borrow::Cow::Borrowed(#lit) });
+        }
+        Tokens::Bytes(bytes) => byte_slice(t, bytes),
+        Tokens::List(kind, items) => {
+            let variant = kind.variant();
+            let items = quote(|t| {
+                for node in items {
+                    let item = quote(|t| item(t, node));
+                    quote!(t, { #item, });
+                }
+            });
+            quote!(t, { ::cor::List::#variant(::std::vec![#items]) });
+        }
+        Tokens::Struct(fields) => entries(t, fields),
+        Tokens::Ext(..) => unreachable!("rejected while parsing"),
+    }
+}
+
+fn byte_slice(t: &mut TokenStream, bytes: &[Literal]) {
+    let bytes = quote(|t| {
+        for byte in bytes {
+            quote!(t, { #byte, });
+        }
+    });
+    quote!(t, { ::std::borrow::Cow::Borrowed(&[#bytes][..]) });
+}
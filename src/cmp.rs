@@ -0,0 +1,281 @@
+use crate::{Entries, List, Value};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+/// Wraps a [`Value`], [`List`] or [`Entries`] (or a reference to one) to give it
+/// `Eq`, `Ord` and `Hash`, so trees can be deduplicated, sorted or used as map keys.
+///
+/// Floats are compared by bit pattern: `NaN` equals itself, `0.0` and `-0.0`
+/// differ, and ordering follows [`f64::total_cmp`]. Values of different types
+/// are ordered by variant, in declaration order.
+#[derive(Debug, Clone, Copy)]
+pub struct Total<T>(pub T);
+
+/// The total order and hash behind [`Total`], implemented for [`Value`], [`List`],
+/// [`Entries`] and the types they hold. Name it as a bound to write code generic
+/// over what `Total` can wrap.
+pub trait TotalOrd {
+    fn total_cmp(&self, other: &Self) -> Ordering;
+    fn total_hash<H: Hasher>(&self, state: &mut H);
+}
+
+impl<T: TotalOrd> PartialEq for Total<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.total_cmp(&other.0).is_eq()
+    }
+}
+
+impl<T: TotalOrd> Eq for Total<T> {}
+
+impl<T: TotalOrd> PartialOrd for Total<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: TotalOrd> Ord for Total<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<T: TotalOrd> Hash for Total<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.total_hash(state);
+    }
+}
+
+impl<T: TotalOrd + ?Sized> TotalOrd for &T {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        (**self).total_cmp(*other)
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        (**self).total_hash(state);
+    }
+}
+
+impl TotalOrd for f32 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl TotalOrd for f64 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl<T: TotalOrd> TotalOrd for [T] {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        for (a, b) in self.iter().zip(other) {
+            match a.total_cmp(b) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+        self.len().cmp(&other.len())
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.len().hash(state);
+        for item in self {
+            item.total_hash(state);
+        }
+    }
+}
+
+impl<T: TotalOrd> TotalOrd for Vec<T> {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self[..].total_cmp(&other[..])
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self[..].total_hash(state);
+    }
+}
+
+/// Types whose own `Ord` and `Hash` are already total.
+macro_rules! total_ord {
+    [$($ty:ty)*] => {$(
+        impl TotalOrd for $ty {
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+
+            fn total_hash<H: Hasher>(&self, state: &mut H) {
+                self.hash(state);
+            }
+        }
+    )*};
+}
+
+total_ord! {
+    bool i64 u64 u16
+    std::borrow::Cow<'_, str>
+    std::borrow::Cow<'_, [u8]>
+}
+
+impl<A: TotalOrd, B: TotalOrd> TotalOrd for (A, B) {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.total_cmp(&other.1))
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.0.total_hash(state);
+        self.1.total_hash(state);
+    }
+}
+
+impl TotalOrd for Value<'_> {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::F32(a), Value::F32(b)) => a.total_cmp(b),
+            (Value::F64(a), Value::F64(b)) => a.total_cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::UInt(a), Value::UInt(b)) => a.cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+            (Value::List(a), Value::List(b)) => a.total_cmp(b),
+            (Value::Struct(a), Value::Struct(b)) => a.total_cmp(b),
+            (Value::Ext { tag: a, data: x }, Value::Ext { tag: b, data: y }) => {
+                a.cmp(b).then_with(|| x.cmp(y))
+            }
+            _ => value_index(self).cmp(&value_index(other)),
+        }
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Value::Bool(val) => val.hash(state),
+            Value::F32(val) => val.total_hash(state),
+            Value::F64(val) => val.total_hash(state),
+            Value::Int(val) => val.hash(state),
+            Value::UInt(val) => val.hash(state),
+            Value::Str(val) => val.hash(state),
+            Value::Bytes(val) => val.hash(state),
+            Value::List(val) => val.total_hash(state),
+            Value::Struct(val) => val.total_hash(state),
+            Value::Ext { tag, data } => {
+                tag.hash(state);
+                data.hash(state);
+            }
+        }
+    }
+}
+
+macro_rules! list_total_ord {
+    [$($name:ident)*] => {
+        impl TotalOrd for List<'_> {
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                match (self, other) {
+                    $((List::$name(a), List::$name(b)) => a.total_cmp(b),)*
+                    _ => list_index(self).cmp(&list_index(other)),
+                }
+            }
+
+            fn total_hash<H: Hasher>(&self, state: &mut H) {
+                std::mem::discriminant(self).hash(state);
+                match self {
+                    $(List::$name(items) => items.total_hash(state),)*
+                }
+            }
+        }
+    };
+}
+
+list_total_ord![Bool F32 F64 Int UInt Str Bytes List Struct];
+
+impl TotalOrd for Entries<'_> {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.fields().total_cmp(other.fields())
+    }
+
+    fn total_hash<H: Hasher>(&self, state: &mut H) {
+        self.fields().total_hash(state);
+    }
+}
+
+/// Position of the variant in declaration order.
+fn value_index(value: &Value) -> u8 {
+    match value {
+        Value::Bool(_) => 0,
+        Value::F32(_) => 1,
+        Value::F64(_) => 2,
+        Value::Int(_) => 3,
+        Value::UInt(_) => 4,
+        Value::Str(_) => 5,
+        Value::Bytes(_) => 6,
+        Value::List(_) => 7,
+        Value::Struct(_) => 8,
+        Value::Ext { .. } => 9,
+    }
+}
+
+fn list_index(list: &List) -> u8 {
+    match list {
+        List::Bool(_) => 0,
+        List::F32(_) => 1,
+        List::F64(_) => 2,
+        List::Int(_) => 3,
+        List::UInt(_) => 4,
+        List::Str(_) => 5,
+        List::Bytes(_) => 6,
+        List::List(_) => 7,
+        List::Struct(_) => 8,
+    }
+}
+
+impl Value<'_> {
+    /// Like `==`, but fields of nested structs may appear in any order.
+    /// List elements are still compared in order.
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::List(a), Value::List(b)) => a.eq_unordered(b),
+            (Value::Struct(a), Value::Struct(b)) => a.eq_unordered(b),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl List<'_> {
+    /// See [`Value::eq_unordered`].
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        fn all<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| eq(a, b))
+        }
+        match (self, other) {
+            (List::List(a), List::List(b)) => all(a, b, List::eq_unordered),
+            (List::Struct(a), List::Struct(b)) => all(a, b, Entries::eq_unordered),
+            (a, b) => a == b,
+        }
+    }
+}
+
+impl Entries<'_> {
+    /// Compares fields regardless of their order, here and in nested structs.
+    /// Values of a repeated key are still compared in order.
+    pub fn eq_unordered(&self, other: &Self) -> bool {
+        fn sorted<'a, 'de>(entries: &'a Entries<'de>) -> Vec<&'a (u16, Value<'de>)> {
+            let mut fields: Vec<_> = entries.fields().iter().collect();
+            fields.sort_by_key(|(key, _)| *key);
+            fields
+        }
+        self.len() == other.len()
+            && (sorted(self).into_iter().zip(sorted(other)))
+                .all(|((a, x), (b, y))| a == b && x.eq_unordered(y))
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{errors::ConvertError, *};
 use std::any::type_name;
+use std::borrow::Cow;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::NonZero;
+use std::path::PathBuf;
 
 macro_rules! convert {
     [$($name:ident($ty:ty))*] => [$(
@@ -30,8 +34,6 @@ convert! {
     F64(f64)
     Int(i64)
     UInt(u64)
-    Str(&'de str)
-    Bytes(&'de [u8])
 }
 
 convert! {
@@ -42,15 +44,70 @@ convert! {
     UInt => u8
     UInt => u16
     UInt => u32
+}
+
+/// Values that own their data (e.g. from [`Value::into_owned`]) can't be
+/// lent out for `'de`, so borrowing conversions only accept borrowed data.
+pub(crate) fn borrowed<'de, T: ?Sized + ToOwned>(
+    val: &Cow<'de, T>,
+    expected: &str,
+) -> Result<&'de T, ConvertError> {
+    match val {
+        Cow::Borrowed(val) => Ok(val),
+        Cow::Owned(_) => Err(ConvertError::conversion(format!(
+            "expected `{expected}`, found owned data"
+        ))),
+    }
+}
+
+impl<'de> ConvertFrom<&Value<'de>> for &'de str {
+    fn convert_from(val: &Value<'de>) -> Result<Self, ConvertError> {
+        match val {
+            Value::Str(val) => borrowed(val, type_name::<Self>()),
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
+
+impl<'de> ConvertFrom<&Value<'de>> for &'de [u8] {
+    fn convert_from(val: &Value<'de>) -> Result<Self, ConvertError> {
+        match val {
+            Value::Bytes(val) => borrowed(val, type_name::<Self>()),
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
 
-    Str => String
-    Bytes => Vec<u8>
+impl ConvertFrom<&Value<'_>> for String {
+    fn convert_from(val: &Value) -> Result<Self, ConvertError> {
+        match val {
+            Value::Str(val) => Ok(val.to_string()),
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for Vec<u8> {
+    fn convert_from(val: &Value) -> Result<Self, ConvertError> {
+        match val {
+            Value::Bytes(val) => Ok(val.to_vec()),
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
 }
 
 pub trait ConvertFrom<T>: Sized {
     fn convert_from(value: T) -> Result<Self, ConvertError>;
 }
 
+/// Converts the value of struct field `key`, recording the key in any error.
+pub fn convert_field<'v, 'de, T>(key: u16, value: Option<&'v Value<'de>>) -> Result<T, ConvertError>
+where
+    T: ConvertFrom<Option<&'v Value<'de>>>,
+{
+    T::convert_from(value).map_err(|err| err.at(Segment::Key(key)))
+}
+
 impl<'v, 'de, T> ConvertFrom<Option<&'v Value<'de>>> for Option<T>
 where
     T: ConvertFrom<&'v Value<'de>>,
@@ -67,7 +124,7 @@ where
     fn convert_from(value: Option<&'v Value<'de>>) -> Result<Self, ConvertError> {
         match value {
             Some(val) => T::convert_from(val),
-            None => Err(ConvertError::new(format!(
+            None => Err(ConvertError::conversion(format!(
                 "expected `{}`, found `None`",
                 type_name::<T>()
             ))),
@@ -81,8 +138,8 @@ where
 {
     fn convert_from(value: &'v Value<'de>) -> Result<Self, ConvertError> {
         match value {
-            Value::Struct(entries) => T::decode(entries).map_err(ConvertError::from),
-            _ => Err(ConvertError::new(format!(
+            Value::Struct(entries) => T::decode(entries),
+            _ => Err(ConvertError::conversion(format!(
                 "expected `{}`, found `None`",
                 type_name::<T>()
             ))),
@@ -92,13 +149,25 @@ where
 
 // -------------------------------------------------------------------------
 
+/// Converts every list element, recording the index of the first failure.
+fn convert_items<'v, I: 'v, T>(
+    items: &'v [I],
+    convert: impl Fn(&'v I) -> Result<T, ConvertError>,
+) -> Result<Vec<T>, ConvertError> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| convert(item).map_err(|err| err.at(Segment::Index(index))))
+        .collect()
+}
+
 impl<'v, 'de, T> ConvertFrom<&'v Value<'de>> for Vec<T>
 where
     T: ConvertFrom<&'v List<'de>>,
 {
     fn convert_from(value: &'v Value<'de>) -> Result<Self, ConvertError> {
         match value {
-            Value::List(List::List(lists)) => lists.iter().map(T::convert_from).collect(),
+            Value::List(List::List(lists)) => convert_items(lists, T::convert_from),
             _ => Err(value.invalid_type(type_name::<Self>())),
         }
     }
@@ -132,11 +201,9 @@ macro_rules! convert_from_list {
             impl<'v, 'de> ConvertFrom<&'v List<'de>> for $ty {
                 fn convert_from(list: &'v List<'de>) -> Result<Self, ConvertError> {
                     match list {
-                        List::$name(items) => items
-                            .iter()
-                            .map(|val| TryFrom::try_from(*val))
-                            .collect::<Result<Vec<_>, _>>()
-                            .map_err(ConvertError::from),
+                        List::$name(items) => convert_items(items, |val| {
+                            TryFrom::try_from(*val).map_err(ConvertError::from)
+                        }),
 
                         _ => Err(list.invalid_type(type_name::<Self>())),
                     }
@@ -159,14 +226,40 @@ convert_from_list! {
     Bool -> Vec<bool>
     F32 -> Vec<f32>
     F64 -> Vec<f64>
-    Str -> Vec<&'de str>
-    Bytes -> Vec<&'de [u8]>
 }
 
-convert_from_list! {
-    Bytes => Vec<Vec<u8>>
-    Str => Vec<String>
+macro_rules! convert_from_list_items {
+    [$($name:ident: $ty:ty => $convert:expr)*] => {
+        $(
+            impl<'v, 'de> ConvertFrom<&'v List<'de>> for $ty {
+                fn convert_from(list: &'v List<'de>) -> Result<Self, ConvertError> {
+                    match list {
+                        List::$name(items) => convert_items(items, $convert),
+                        _ => Err(list.invalid_type(type_name::<Self>())),
+                    }
+                }
+            }
+
+            impl<'v, 'de> ConvertFrom<&'v Value<'de>> for $ty {
+                fn convert_from(value: &'v Value<'de>) -> Result<Self, ConvertError> {
+                    match value {
+                        Value::List(list) => Self::convert_from(list),
+                        _ => Err(value.invalid_type(type_name::<Self>())),
+                    }
+                }
+            }
+        )*
+    };
+}
 
+convert_from_list_items! {
+    Str: Vec<&'de str> => |item| borrowed(item, "&str")
+    Bytes: Vec<&'de [u8]> => |item| borrowed(item, "&[u8]")
+    Str: Vec<String> => |item| Ok(item.to_string())
+    Bytes: Vec<Vec<u8>> => |item| Ok(item.to_vec())
+}
+
+convert_from_list! {
     // UInt => Vec<u8>
     UInt => Vec<u16>
     UInt => Vec<u32>
@@ -184,8 +277,145 @@ where
 {
     fn convert_from(list: &'v List<'de>) -> Result<Self, ConvertError> {
         match list {
-            List::List(items) => items.iter().map(T::convert_from).collect(),
+            List::List(items) => convert_items(items, T::convert_from),
             _ => Err(list.invalid_type(type_name::<Self>())),
         }
     }
 }
+
+// -------------------------------------------------------------------------
+
+fn octets<const N: usize>(value: &Value, expected: &str) -> Result<[u8; N], ConvertError> {
+    match value {
+        Value::Bytes(bytes) => bytes[..].try_into().map_err(|_| {
+            ConvertError::conversion(format!(
+                "expected {N} bytes for `{expected}`, found {}",
+                bytes.len()
+            ))
+        }),
+        val => Err(val.invalid_type(expected)),
+    }
+}
+
+/// Splits a socket address into its IP octets, port and whatever follows them.
+fn socket_addr<'v, const N: usize>(
+    value: &'v Value,
+    lens: &[usize],
+    expected: &str,
+) -> Result<([u8; N], u16, &'v [u8]), ConvertError> {
+    match value {
+        Value::Bytes(bytes) if lens.contains(&bytes.len()) => {
+            let (ip, rest) = bytes.split_at(N);
+            let (port, rest) = rest.split_at(2);
+            Ok((
+                ip.try_into().unwrap(),
+                u16::from_be_bytes(port.try_into().unwrap()),
+                rest,
+            ))
+        }
+        Value::Bytes(bytes) => Err(wrong_len(lens, expected, bytes.len())),
+        val => Err(val.invalid_type(expected)),
+    }
+}
+
+fn wrong_len(lens: &[usize], expected: &str, found: usize) -> ConvertError {
+    let lens = match lens {
+        [len] => len.to_string(),
+        [lens @ .., last] => {
+            let lens: Vec<_> = lens.iter().map(usize::to_string).collect();
+            format!("{} or {last}", lens.join(", "))
+        }
+        [] => unreachable!(),
+    };
+    ConvertError::conversion(format!(
+        "expected {lens} bytes for `{expected}`, found {found}"
+    ))
+}
+
+impl ConvertFrom<&Value<'_>> for Ipv4Addr {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        octets::<4>(value, type_name::<Self>()).map(Ipv4Addr::from)
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for Ipv6Addr {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        octets::<16>(value, type_name::<Self>()).map(Ipv6Addr::from)
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for IpAddr {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        match value {
+            Value::Bytes(bytes) => match bytes.len() {
+                4 => Ipv4Addr::convert_from(value).map(IpAddr::V4),
+                16 => Ipv6Addr::convert_from(value).map(IpAddr::V6),
+                len => Err(wrong_len(&[4, 16], type_name::<Self>(), len)),
+            },
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for SocketAddrV4 {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        let (ip, port, _) = socket_addr::<4>(value, &[6], type_name::<Self>())?;
+        Ok(SocketAddrV4::new(ip.into(), port))
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for SocketAddrV6 {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        let (ip, port, rest) = socket_addr::<16>(value, &[18, 26], type_name::<Self>())?;
+        let (flowinfo, scope_id) = match rest {
+            [] => (0, 0),
+            rest => {
+                let (flowinfo, scope_id) = rest.split_at(4);
+                (
+                    u32::from_be_bytes(flowinfo.try_into().unwrap()),
+                    u32::from_be_bytes(scope_id.try_into().unwrap()),
+                )
+            }
+        };
+        Ok(SocketAddrV6::new(ip.into(), port, flowinfo, scope_id))
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for SocketAddr {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        match value {
+            Value::Bytes(bytes) => match bytes.len() {
+                6 => SocketAddrV4::convert_from(value).map(SocketAddr::V4),
+                18 | 26 => SocketAddrV6::convert_from(value).map(SocketAddr::V6),
+                len => Err(wrong_len(&[6, 18, 26], type_name::<Self>(), len)),
+            },
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
+
+impl ConvertFrom<&Value<'_>> for PathBuf {
+    fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+        match value {
+            Value::Str(path) => Ok(PathBuf::from(&**path)),
+            val => Err(val.invalid_type(type_name::<Self>())),
+        }
+    }
+}
+
+macro_rules! convert_non_zero {
+    [$($ty:ty)*] => [$(
+        impl ConvertFrom<&Value<'_>> for NonZero<$ty> {
+            fn convert_from(value: &Value) -> Result<Self, ConvertError> {
+                NonZero::new(<$ty>::convert_from(value)?).ok_or_else(|| {
+                    ConvertError::conversion(format!(
+                        "expected `{}`, found `0`",
+                        type_name::<Self>()
+                    ))
+                })
+            }
+        }
+    )*];
+}
+
+convert_non_zero! { u8 u16 u32 u64 i8 i16 i32 i64 }
@@ -0,0 +1,611 @@
+// Reads the serde data model back from the mapping described in `ser.rs`.
+
+use crate::{DecodeOptions, Entries, Error, List, Result, Segment, Value, ser::key};
+use serde::de::{self, Deserialize, IntoDeserializer, Visitor};
+use std::borrow::Cow;
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::conversion(msg.to_string())
+    }
+}
+
+/// Deserializes a message that must span all of `bytes`. Strings and bytes can
+/// be borrowed from `bytes`.
+pub fn from_slice<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> Result<T> {
+    from_slice_with(bytes, &DecodeOptions::default())
+}
+
+pub fn from_slice_with<'de, T: Deserialize<'de>>(
+    bytes: &'de [u8],
+    options: &DecodeOptions,
+) -> Result<T> {
+    let entries = Entries::from_slice_exact_with(bytes, options)?;
+    T::deserialize(Value::Struct(entries))
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Reads a value with the same mapping as [`from_slice`], so a message that
+/// was parsed dynamically can be deserialized into a concrete type later.
+impl<'de> de::Deserializer<'de> for Value<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Bool(val) => visitor.visit_bool(val),
+            Value::F32(val) => visitor.visit_f32(val),
+            Value::F64(val) => visitor.visit_f64(val),
+            Value::Int(val) => visitor.visit_i64(val),
+            Value::UInt(val) => visitor.visit_u64(val),
+            Value::Str(Cow::Borrowed(val)) => visitor.visit_borrowed_str(val),
+            Value::Str(Cow::Owned(val)) => visitor.visit_string(val),
+            Value::Bytes(Cow::Borrowed(val)) => visitor.visit_borrowed_bytes(val),
+            Value::Bytes(Cow::Owned(val)) => visitor.visit_byte_buf(val),
+            Value::List(list) => visitor.visit_seq(SeqAccess::new(list)),
+            Value::Struct(entries) => {
+                visitor.visit_map(MapAccess::new(entries, |key| Ok(Value::UInt(key.into()))))
+            }
+            Value::Ext { tag, data } => {
+                let fields = [
+                    (Value::Str("tag".into()), Value::UInt(tag)),
+                    (Value::Str("data".into()), Value::Bytes(data)),
+                ];
+                visitor.visit_map(de::value::MapDeserializer::new(fields.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    /// Bytes are also a sequence of `u8`, which is how serde sees a `Vec<u8>`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Bytes(Cow::Borrowed(val)) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(val.iter().copied()))
+            }
+            Value::Bytes(Cow::Owned(val)) => {
+                visitor.visit_seq(de::value::SeqDeserializer::new(val.into_iter()))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Struct(entries) if entries.is_empty() => visitor.visit_unit(),
+            _ => Err(self.invalid_type("unit")),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Struct(mut entries) => {
+                let fields = (0..len).map(|index| {
+                    let key = key("", index)?;
+                    let value = entries.remove(key);
+                    value.ok_or_else(|| de::Error::invalid_length(index, &visitor_len(len)))
+                });
+                let fields: Result<Vec<_>> = fields.collect();
+                visitor.visit_seq(de::value::SeqDeserializer::new(fields?.into_iter()))
+            }
+            _ => Err(self.invalid_type("tuple")),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::List(List::Struct(items)) => {
+                visitor.visit_map(de::value::MapDeserializer::new(items.into_iter().map(
+                    |mut entry| {
+                        let key = entry.remove(0).unwrap_or(Value::Struct(Entries::new()));
+                        // An absent value is a `None`.
+                        let value = entry.remove(1);
+                        (key, OptionalValue(value))
+                    },
+                )))
+            }
+            Value::List(list) if list_is_empty(&list) => visitor.visit_map(
+                de::value::MapDeserializer::new(std::iter::empty::<(Value, Value)>()),
+            ),
+            Value::Struct(entries) => {
+                visitor.visit_map(MapAccess::new(entries, |key| Ok(Value::UInt(key.into()))))
+            }
+            _ => Err(self.invalid_type("map")),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Value::Struct(entries) => visitor.visit_map(MapAccess::new(entries, |key| {
+                Ok(Value::Str(Cow::Borrowed(
+                    name_of(fields, key).unwrap_or(""),
+                )))
+            })),
+            _ => Err(self.invalid_type("struct")),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let (key, payload) = match self {
+            Value::UInt(key) => (key, None),
+            Value::Struct(entries) if entries.len() == 1 => {
+                let (key, value) = entries.into_iter().next().unwrap();
+                (key.into(), Some(value))
+            }
+            _ => return Err(self.invalid_type("enum")),
+        };
+        let variant = u16::try_from(key)
+            .ok()
+            .and_then(|key| name_of(variants, key))
+            .ok_or_else(|| Error::conversion(format!("unknown variant key `{key}`")))?;
+        visitor.visit_enum(EnumAccess { variant, payload })
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf identifier
+    }
+}
+
+fn visitor_len(len: usize) -> impl de::Expected {
+    struct Len(usize);
+    impl de::Expected for Len {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "a tuple of {} fields", self.0)
+        }
+    }
+    Len(len)
+}
+
+/// The field or variant among `names` that is stored under `key`.
+fn name_of(names: &'static [&'static str], key: u16) -> Option<&'static str> {
+    let explicit = names.iter().find(|name| name.parse() == Ok(key));
+    let positional = names
+        .get(key as usize)
+        .filter(|name| name.parse::<u16>().is_err());
+    explicit.or(positional).copied()
+}
+
+fn list_is_empty(list: &List) -> bool {
+    match list {
+        List::Bool(items) => items.is_empty(),
+        List::F32(items) => items.is_empty(),
+        List::F64(items) => items.is_empty(),
+        List::Int(items) => items.is_empty(),
+        List::UInt(items) => items.is_empty(),
+        List::Str(items) => items.is_empty(),
+        List::Bytes(items) => items.is_empty(),
+        List::List(items) => items.is_empty(),
+        List::Struct(items) => items.is_empty(),
+    }
+}
+
+/// A map value that may be absent, which deserializes as `None`.
+struct OptionalValue<'de>(Option<Value<'de>>);
+
+impl<'de> IntoDeserializer<'de, Error> for OptionalValue<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for OptionalValue<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(value) => value.deserialize_any(visitor),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(value) => visitor.visit_some(value),
+            None => visitor.visit_none(),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+struct SeqAccess<'de> {
+    items: std::vec::IntoIter<Value<'de>>,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> {
+    fn new(list: List<'de>) -> Self {
+        fn values<'de, T>(items: Vec<T>, f: fn(T) -> Value<'de>) -> Vec<Value<'de>> {
+            items.into_iter().map(f).collect()
+        }
+        let items = match list {
+            List::Bool(items) => values(items, Value::Bool),
+            List::F32(items) => values(items, Value::F32),
+            List::F64(items) => values(items, Value::F64),
+            List::Int(items) => values(items, Value::Int),
+            List::UInt(items) => values(items, Value::UInt),
+            List::Str(items) => values(items, Value::Str),
+            List::Bytes(items) => values(items, Value::Bytes),
+            List::List(items) => values(items, Value::List),
+            List::Struct(items) => values(items, Value::Struct),
+        };
+        Self {
+            items: items.into_iter(),
+            index: 0,
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        let Some(item) = self.items.next() else {
+            return Ok(None);
+        };
+        let index = self.index;
+        self.index += 1;
+        seed.deserialize(item)
+            .map(Some)
+            .map_err(|err| err.at(Segment::Index(index)))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+/// Presents struct fields as map entries, with keys produced by `key`; keys for
+/// which it yields an empty string are skipped.
+struct MapAccess<'de, F> {
+    fields: crate::entries::IntoIter<'de>,
+    key: F,
+    value: Option<(u16, Value<'de>)>,
+}
+
+impl<'de, F: FnMut(u16) -> Result<Value<'de>>> MapAccess<'de, F> {
+    fn new(entries: Entries<'de>, key: F) -> Self {
+        Self {
+            fields: entries.into_iter(),
+            key,
+            value: None,
+        }
+    }
+}
+
+impl<'de, F: FnMut(u16) -> Result<Value<'de>>> de::MapAccess<'de> for MapAccess<'de, F> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        for (key, value) in self.fields.by_ref() {
+            let name = (self.key)(key)?;
+            if matches!(&name, Value::Str(name) if name.is_empty()) {
+                continue;
+            }
+            self.value = Some((key, value));
+            return seed.deserialize(name).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let (key, value) = self.value.take().expect("value requested before key");
+        seed.deserialize(value)
+            .map_err(|err| err.at(Segment::Key(key)))
+    }
+}
+
+struct EnumAccess<'de> {
+    variant: &'static str,
+    payload: Option<Value<'de>>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = VariantAccess<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant)> {
+        let variant =
+            seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.variant))?;
+        Ok((variant, VariantAccess(self.payload)))
+    }
+}
+
+struct VariantAccess<'de>(Option<Value<'de>>);
+
+impl<'de> VariantAccess<'de> {
+    fn payload(self) -> Result<Value<'de>> {
+        self.0.ok_or_else(|| {
+            Error::conversion("expected a variant with fields, found a unit variant")
+        })
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.0 {
+            None => Ok(()),
+            Some(value) => de::Deserialize::deserialize(value),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.payload()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_tuple(self.payload()?, len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_struct(self.payload()?, "", fields, visitor)
+    }
+}
+
+/// Reads any self-describing input: integers become `Int`, or `UInt` when
+/// unsigned; sequences become lists, whose elements must all have the same
+/// type; maps keyed by integers (or decimal strings) become structs, and
+/// `{tag, data}` maps become extension values. A list holding both `Int` and
+/// `UInt` becomes a list of `Int`. Unit, which is how many formats read `null`,
+/// is an empty struct; `None` is rejected.
+impl<'de> Deserialize<'de> for Value<'de> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Entries<'de> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match deserializer.deserialize_map(ValueVisitor)? {
+            Value::Struct(entries) => Ok(entries),
+            value => Err(de::Error::invalid_type(
+                de::Unexpected::Other(value.type_name()),
+                &"a struct",
+            )),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+macro_rules! visit {
+    [$($method:ident($ty:ty) => $name:ident,)*] => {$(
+        fn $method<E: de::Error>(self, val: $ty) -> Result<Value<'de>, E> {
+            Ok(Value::$name(val.into()))
+        }
+    )*};
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value<'de>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a cor value")
+    }
+
+    visit! {
+        visit_bool(bool) => Bool,
+        visit_i64(i64) => Int,
+        visit_u64(u64) => UInt,
+        visit_f32(f32) => F32,
+        visit_f64(f64) => F64,
+        visit_borrowed_str(&'de str) => Str,
+        visit_string(String) => Str,
+        visit_borrowed_bytes(&'de [u8]) => Bytes,
+        visit_byte_buf(Vec<u8>) => Bytes,
+    }
+
+    fn visit_str<E: de::Error>(self, val: &str) -> Result<Value<'de>, E> {
+        Ok(Value::Str(Cow::Owned(val.to_string())))
+    }
+
+    fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Value<'de>, E> {
+        Ok(Value::Bytes(Cow::Owned(val.to_vec())))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value<'de>, E> {
+        Ok(Value::Struct(Entries::new()))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value<'de>, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value<'de>, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value<'de>, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        if items.iter().any(|item| matches!(item, Value::Int(_))) {
+            for item in &mut items {
+                if let Value::UInt(val) = *item {
+                    *item = Value::Int(i64::try_from(val).map_err(de::Error::custom)?);
+                }
+            }
+        }
+        let list = crate::ser::list(items).map_err(de::Error::custom)?;
+        Ok(Value::List(list))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value<'de>, A::Error> {
+        let mut entries = Entries::new();
+        let (mut tag, mut data) = (None, None);
+        while let Some(key) = map.next_key()? {
+            match key {
+                FieldKey::Key(key) => {
+                    entries.insert(key, map.next_value()?);
+                }
+                FieldKey::Tag if tag.is_none() => tag = Some(map.next_value()?),
+                FieldKey::Data if data.is_none() => data = Some(map.next_value::<Data>()?.0),
+                FieldKey::Tag => return Err(de::Error::duplicate_field("tag")),
+                FieldKey::Data => return Err(de::Error::duplicate_field("data")),
+            }
+        }
+        match (tag, data) {
+            (None, None) => Ok(Value::Struct(entries)),
+            (Some(tag), Some(data)) if entries.is_empty() => Ok(Value::Ext { tag, data }),
+            _ => Err(de::Error::custom(
+                "an extension value must have exactly a `tag` and `data`",
+            )),
+        }
+    }
+}
+
+/// A map key: a struct key, or a field of an extension value.
+enum FieldKey {
+    Key(u16),
+    Tag,
+    Data,
+}
+
+impl<'de> Deserialize<'de> for FieldKey {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = FieldKey;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a struct key")
+            }
+
+            fn visit_u64<E: de::Error>(self, val: u64) -> Result<FieldKey, E> {
+                u16::try_from(val)
+                    .map(FieldKey::Key)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(val), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, val: i64) -> Result<FieldKey, E> {
+                u16::try_from(val)
+                    .map(FieldKey::Key)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(val), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, val: &str) -> Result<FieldKey, E> {
+                match val {
+                    "tag" => Ok(FieldKey::Tag),
+                    "data" => Ok(FieldKey::Data),
+                    _ => val
+                        .parse()
+                        .map(FieldKey::Key)
+                        .map_err(|_| E::invalid_value(de::Unexpected::Str(val), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(KeyVisitor)
+    }
+}
+
+/// The data of an extension value, as bytes or a sequence of `u8`.
+struct Data<'de>(Cow<'de, [u8]>);
+
+impl<'de> Deserialize<'de> for Data<'de> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DataVisitor;
+
+        impl<'de> Visitor<'de> for DataVisitor {
+            type Value = Data<'de>;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_borrowed_bytes<E: de::Error>(self, val: &'de [u8]) -> Result<Data<'de>, E> {
+                Ok(Data(Cow::Borrowed(val)))
+            }
+
+            fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<Data<'de>, E> {
+                Ok(Data(Cow::Owned(val.to_vec())))
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, val: Vec<u8>) -> Result<Data<'de>, E> {
+                Ok(Data(Cow::Owned(val)))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Data<'de>, A::Error> {
+                let mut data = Vec::new();
+                while let Some(byte) = seq.next_element()? {
+                    data.push(byte);
+                }
+                Ok(Data(Cow::Owned(data)))
+            }
+        }
+
+        deserializer.deserialize_bytes(DataVisitor)
+    }
+}
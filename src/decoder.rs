@@ -1,95 +1,909 @@
 use crate::*;
+use convert::borrowed;
+use path::Segment;
+use std::any::type_name;
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::str::Utf8Error;
 
 use super::Result;
 
-fn parse_header(reader: &mut &[u8]) -> Result<(u64, u8)> {
-    let byte = utils::read_byte(reader)?;
+/// Where a [`Parser`] reads its bytes from: a slice that values borrow from,
+/// or a stream they are copied out of.
+pub trait Source<'de> {
+    fn read_byte(&mut self) -> Result<u8>;
 
-    let ty = byte & 0b00001111;
-    let id = (byte >> 4) as u64;
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]>;
 
-    let id = if id == 0b1111 {
-        varint::read_unsigned(reader)? + 15
-    } else {
-        id
-    };
-    Ok((id, ty))
+    /// Consumes the next `len` bytes, borrowing them for `'de` if the source can.
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>>;
+
+    /// Counts the bytes consumed so far, from an origin of the source's choosing.
+    fn position(&self) -> usize;
+
+    /// Bounds the bytes left, so a length prefix alone can't reserve much memory.
+    fn remaining(&self) -> usize;
+}
+
+impl<'de> Source<'de> for &mut &'de [u8] {
+    fn read_byte(&mut self) -> Result<u8> {
+        utils::read_byte(self)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        utils::read_buf(self)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        utils::read_bytes(self, len).map(Cow::Borrowed)
+    }
+
+    fn position(&self) -> usize {
+        // The slice shrinks as it's read.
+        usize::MAX - self.len()
+    }
+
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+}
+
+/// The keys seen in one struct. Structs rarely have many fields, so the first
+/// few keys are kept inline and checking them doesn't allocate.
+#[derive(Default)]
+struct KeySet {
+    inline: [u16; 8],
+    len: usize,
+    spilled: HashSet<u16>,
 }
 
-fn parse_str<'de>(reader: &mut &'de [u8]) -> Result<&'de str> {
-    Ok(parse_bytes(reader).map(str::from_utf8)??)
+impl KeySet {
+    /// Returns whether `key` wasn't seen before.
+    fn insert(&mut self, key: u16) -> bool {
+        if self.inline[..self.len].contains(&key) {
+            return false;
+        }
+        match self.inline.get_mut(self.len) {
+            Some(slot) => {
+                *slot = key;
+                self.len += 1;
+                true
+            }
+            None => self.spilled.insert(key),
+        }
+    }
 }
 
-fn parse_bytes<'de>(reader: &mut &'de [u8]) -> Result<&'de [u8]> {
-    let len = varint::read_unsigned(reader).map(u32::try_from)??;
-    utils::read_bytes(reader, len.try_into().unwrap())
+/// Parsing state shared by every nested value of a message.
+pub struct Parser<'a, 'de, R = &'a mut &'de [u8]> {
+    reader: R,
+    options: &'a DecodeOptions,
+    depth: usize,
+    start: usize,
+    elements: usize,
+    /// Keys of the struct being decoded that were skipped as unwanted.
+    skipped_keys: KeySet,
+    // Filled innermost first while an error unwinds, so the happy path pays nothing.
+    error_path: Vec<Segment>,
+    error_offset: Option<usize>,
+    borrowed: PhantomData<&'de [u8]>,
 }
 
-fn collect<T>(len: u32, mut f: impl FnMut() -> Result<T>) -> Result<Vec<T>> {
-    let mut arr = Vec::with_capacity(len.try_into().unwrap());
-    for _ in 0..len {
-        arr.push(f()?);
+impl<'a, 'de> Parser<'a, 'de> {
+    /// Creates a parser positioned inside the outermost struct.
+    pub fn new(reader: &'a mut &'de [u8], options: &'a DecodeOptions) -> Self {
+        Self::over(reader, options)
+    }
+
+    /// Creates a parser for a value nested `depth` levels deep, e.g. one that an
+    /// [`EntriesRef`] decodes after indexing.
+    pub(crate) fn at_depth(
+        reader: &'a mut &'de [u8],
+        options: &'a DecodeOptions,
+        depth: usize,
+    ) -> Self {
+        Self {
+            depth,
+            ..Self::new(reader, options)
+        }
     }
-    Ok(arr)
 }
 
-fn parse_list<'de>(reader: &mut &'de [u8]) -> Result<List<'de>> {
-    let (len, ty) = parse_header(reader)?;
-    let len = u32::try_from(len)?;
+impl<'a, 'de, R: Source<'de>> Parser<'a, 'de, R> {
+    /// Creates a parser positioned inside the outermost struct of `reader`.
+    pub(crate) fn over(reader: R, options: &'a DecodeOptions) -> Self {
+        Self {
+            start: reader.position(),
+            reader,
+            options,
+            depth: 1,
+            elements: 0,
+            skipped_keys: KeySet::default(),
+            error_path: Vec::new(),
+            error_offset: None,
+            borrowed: PhantomData,
+        }
+    }
+
+    /// Records that `err` happened inside `segment` of the value being parsed.
+    fn at(&mut self, segment: Segment, err: Error) -> Error {
+        self.error_path.push(segment);
+        err
+    }
+
+    fn fail_at(&mut self, offset: usize, err: Error) -> Error {
+        self.error_offset = Some(offset);
+        err
+    }
+
+    /// Attaches the field path of the failure to `err`, and the byte offset
+    /// unless the bytes were fine but didn't convert.
+    pub(crate) fn locate(&mut self, err: Error) -> Error {
+        let mut path = std::mem::take(&mut self.error_path);
+        path.reverse();
+        let offset = self.error_offset.take().unwrap_or_else(|| self.consumed());
+        let offset = match err.kind() {
+            ErrorKind::Conversion { .. } => None,
+            _ => Some(offset),
+        };
+        err.located(offset, path.into())
+    }
+
+    fn consumed(&self) -> usize {
+        self.reader.position() - self.start
+    }
+
+    fn check_bytes(&self, upcoming: usize) -> Result<()> {
+        match self.consumed().checked_add(upcoming) {
+            Some(len) if len <= self.options.max_bytes => Ok(()),
+            _ => Err(limit_exceeded("max_bytes", self.options.max_bytes)),
+        }
+    }
+
+    fn count_elements(&mut self, count: usize) -> Result<()> {
+        match self.elements.checked_add(count) {
+            Some(elements) if elements <= self.options.max_elements => {
+                self.elements = elements;
+                Ok(())
+            }
+            _ => Err(limit_exceeded("max_elements", self.options.max_elements)),
+        }
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth >= self.options.max_depth {
+            return Err(ErrorKind::DepthLimitExceeded {
+                max_depth: self.options.max_depth,
+            }
+            .into());
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
 
-    match ty {
-        0 | 1 => collect(len, || match utils::read_byte(reader)? {
+    fn header(&mut self) -> Result<(u64, u8)> {
+        varint::decode_header(|| self.reader.read_byte())
+    }
+
+    /// Reads the next field header of a struct, returning `None` at the end of the struct.
+    pub fn next_field(&mut self) -> Result<Option<(u16, u8)>> {
+        let header = self.header()?;
+        self.check_bytes(0)?;
+        match header {
+            (key, 10) => {
+                debug_assert!(key == 0);
+                Ok(None) // End of struct
+            }
+            (key, ty) => {
+                self.count_elements(1)?;
+                let key = u16::try_from(key).map_err(|_| ErrorKind::InvalidKey { key })?;
+                Ok(Some((key, ty)))
+            }
+        }
+    }
+
+    fn bool(&mut self) -> Result<bool> {
+        match self.reader.read_byte()? {
             0 => Ok(false),
             1 => Ok(true),
-            v => Err(errors::ParseError::new(format!("invalid boolean value: `{v}`")).into()),
+            value => {
+                Err(self.fail_at(self.consumed() - 1, ErrorKind::InvalidBool { value }.into()))
+            }
+        }
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        self.reader.read_array().map(f32::from_le_bytes)
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        self.reader.read_array().map(f64::from_le_bytes)
+    }
+
+    fn int(&mut self) -> Result<i64> {
+        self.unsigned().map(zig_zag::from)
+    }
+
+    fn unsigned(&mut self) -> Result<u64> {
+        varint::decode_unsigned(|| self.reader.read_byte())
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        let len = self.unsigned()?;
+        checked_len(len)
+    }
+
+    /// Reports where the `len` bytes just read stop being UTF-8.
+    fn utf8<T>(&mut self, len: usize, result: Result<T, Utf8Error>) -> Result<T> {
+        result.map_err(|err| {
+            let offset = self.consumed() - len + err.valid_up_to();
+            self.fail_at(offset, Error::utf8(err))
         })
-        .map(List::Bool),
-        2 => collect(len, || utils::read_buf(reader).map(f32::from_le_bytes)).map(List::F32),
-        3 => collect(len, || utils::read_buf(reader).map(f64::from_le_bytes)).map(List::F64),
-        4 => collect(len, || varint::read_unsigned(reader).map(zig_zag::from)).map(List::Int),
-        5 => collect(len, || varint::read_unsigned(reader)).map(List::UInt),
-        6 => collect(len, || parse_str(reader)).map(List::Str),
-        7 => collect(len, || parse_bytes(reader)).map(List::Bytes),
-        8 => collect(len, || parse_list(reader)).map(List::List),
-        9 => collect(len, || Entries::parse(reader)).map(List::Struct),
-        code => Err(errors::UnknownType { code }.into()),
     }
-}
 
-impl<'de> Entries<'de> {
-    pub fn parse(reader: &mut &'de [u8]) -> Result<Self> {
+    fn str(&mut self) -> Result<Cow<'de, str>> {
+        let len = self.len()?;
+        self.check_bytes(len)?;
+        let result = match self.reader.read_bytes(len)? {
+            Cow::Borrowed(bytes) => str::from_utf8(bytes).map(Cow::Borrowed),
+            Cow::Owned(bytes) => String::from_utf8(bytes)
+                .map(Cow::Owned)
+                .map_err(|err| err.utf8_error()),
+        };
+        self.utf8(len, result)
+    }
+
+    fn bytes(&mut self) -> Result<Cow<'de, [u8]>> {
+        let len = self.len()?;
+        self.check_bytes(len)?;
+        self.reader.read_bytes(len)
+    }
+
+    fn ext(&mut self) -> Result<Value<'de>> {
+        let tag = self.unsigned()?;
+        let data = self.bytes()?;
+        Ok(Value::Ext { tag, data })
+    }
+
+    fn list_header(&mut self) -> Result<(usize, u8)> {
+        let (len, ty) = self.header()?;
+        let len = checked_len(len)?;
+        if len > self.options.max_list_len {
+            return Err(limit_exceeded("max_list_len", self.options.max_list_len));
+        }
+        self.count_elements(len)?;
+        Ok((len, ty))
+    }
+
+    fn collect<T>(
+        &mut self,
+        len: usize,
+        mut f: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        // Every element takes at least one byte, so the remaining input bounds the capacity.
+        let mut arr = Vec::with_capacity(len.min(self.reader.remaining()));
+        for index in 0..len {
+            match f(self) {
+                Ok(item) => arr.push(item),
+                Err(err) => return Err(self.at(Segment::Index(index), err)),
+            }
+        }
+        Ok(arr)
+    }
+
+    fn list(&mut self) -> Result<List<'de>> {
+        let (len, ty) = self.list_header()?;
+
+        match ty {
+            0 | 1 => self.collect(len, Self::bool).map(List::Bool),
+            2 => self.collect(len, Self::f32).map(List::F32),
+            3 => self.collect(len, Self::f64).map(List::F64),
+            4 => self.collect(len, Self::int).map(List::Int),
+            5 => self.collect(len, Self::unsigned).map(List::UInt),
+            6 => self.collect(len, Self::str).map(List::Str),
+            7 => self.collect(len, Self::bytes).map(List::Bytes),
+            8 => self.collect(len, |p| p.nested(Self::list)).map(List::List),
+            9 => self
+                .collect(len, |p| p.nested(Self::entries))
+                .map(List::Struct),
+            code => Err(ErrorKind::UnknownType { code }.into()),
+        }
+    }
+
+    /// Parses the remaining fields of the current struct.
+    pub(crate) fn entries(&mut self) -> Result<Entries<'de>> {
+        self.entries_where(|_| true)
+    }
+
+    /// Parses the remaining fields of the current struct whose key passes `keep`,
+    /// skipping the others without allocating them. Only the outermost struct is
+    /// filtered, which is what skipped keys are tracked for.
+    fn entries_where(&mut self, mut keep: impl FnMut(u16) -> bool) -> Result<Entries<'de>> {
         let mut entries = Entries::new();
-        loop {
-            let (key, ty) = parse_header(reader)?;
-            let value = match ty {
-                0 => Ok(Value::Bool(false)),
-                1 => Ok(Value::Bool(true)),
-
-                2 => utils::read_buf(reader)
-                    .map(f32::from_le_bytes)
-                    .map(Value::F32),
-
-                3 => utils::read_buf(reader)
-                    .map(f64::from_le_bytes)
-                    .map(Value::F64),
-
-                4 => varint::read_unsigned(reader)
-                    .map(zig_zag::from)
-                    .map(Value::Int),
-
-                5 => varint::read_unsigned(reader).map(Value::UInt),
-                6 => parse_str(reader).map(Value::Str),
-                7 => parse_bytes(reader).map(Value::Bytes),
-                8 => parse_list(reader).map(Value::List),
-                9 => Entries::parse(reader).map(Value::Struct),
-                10 => {
-                    debug_assert!(key == 0);
-                    break; // End of struct
+        while let Some((key, ty)) = self.next_field()? {
+            if !keep(key) {
+                self.skip_unknown_field(key, ty)?;
+                continue;
+            }
+            let value = self
+                .value(ty)
+                .map_err(|err| self.at(Segment::Key(key), err))?;
+            entries.push(key, value);
+        }
+        entries
+            .resolve_duplicates(self.options.duplicate_keys)
+            .map_err(|key| self.duplicate(key))?;
+        Ok(entries)
+    }
+
+    fn duplicate(&mut self, key: u16) -> Error {
+        self.at(Segment::Key(key), ErrorKind::DuplicateKey { key }.into())
+    }
+
+    /// Parses the value of a struct field whose header has type `ty`.
+    fn value(&mut self, ty: u8) -> Result<Value<'de>> {
+        match ty {
+            0 => Ok(Value::Bool(false)),
+            1 => Ok(Value::Bool(true)),
+            2 => self.f32().map(Value::F32),
+            3 => self.f64().map(Value::F64),
+            4 => self.int().map(Value::Int),
+            5 => self.unsigned().map(Value::UInt),
+            6 => self.str().map(Value::Str),
+            7 => self.bytes().map(Value::Bytes),
+            8 => self.nested(Self::list).map(Value::List),
+            9 => self.nested(Self::entries).map(Value::Struct),
+            11 => self.ext(),
+            code => Err(ErrorKind::UnknownType { code }.into()),
+        }
+    }
+
+    fn skip_str(&mut self) -> Result<()> {
+        let len = self.len()?;
+        self.check_bytes(len)?;
+        let result = str::from_utf8(&self.reader.read_bytes(len)?).map(drop);
+        self.utf8(len, result)
+    }
+
+    fn skip_bytes(&mut self) -> Result<()> {
+        self.bytes().map(drop)
+    }
+
+    fn skip_list(&mut self) -> Result<()> {
+        let (len, ty) = self.list_header()?;
+        self.skip_elements(len, ty)
+    }
+
+    fn skip_elements(&mut self, len: usize, ty: u8) -> Result<()> {
+        for index in 0..len {
+            let result = match ty {
+                0 | 1 => self.bool().map(drop),
+                2..=9 => self.skip(ty),
+                code => return Err(ErrorKind::UnknownType { code }.into()),
+            };
+            result.map_err(|err| self.at(Segment::Index(index), err))?;
+        }
+        Ok(())
+    }
+
+    /// Skips the remaining fields of the current struct, rejecting a repeated key
+    /// under [`DuplicateKeys::Reject`] as [`Entries::parse`] would.
+    fn skip_struct(&mut self) -> Result<()> {
+        let mut keys = KeySet::default();
+        while let Some((key, ty)) = self.next_field()? {
+            if self.options.duplicate_keys == DuplicateKeys::Reject && !keys.insert(key) {
+                return Err(self.duplicate(key));
+            }
+            self.skip(ty)
+                .map_err(|err| self.at(Segment::Key(key), err))?;
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, ty: u8) -> Result<()> {
+        match ty {
+            0 | 1 => Ok(()),
+            2 => self.f32().map(drop),
+            3 => self.f64().map(drop),
+            4 | 5 => self.unsigned().map(drop),
+            6 => self.skip_str(),
+            7 => self.skip_bytes(),
+            8 => self.nested(Self::skip_list),
+            9 => self.nested(Self::skip_struct),
+            11 => {
+                self.unsigned()?;
+                self.skip_bytes()
+            }
+            code => Err(ErrorKind::UnknownType { code }.into()),
+        }
+    }
+
+    /// Skips struct field `key`, which the type being decoded doesn't declare.
+    /// A repeated key is still rejected under [`DuplicateKeys::Reject`], as
+    /// [`Entries::parse`] would.
+    pub fn skip_unknown_field(&mut self, key: u16, ty: u8) -> Result<()> {
+        if self.options.duplicate_keys == DuplicateKeys::Reject && !self.skipped_keys.insert(key) {
+            return Err(self.duplicate(key));
+        }
+        self.skip(ty).map_err(|err| self.at(Segment::Key(key), err))
+    }
+
+    /// Decodes the value of struct field `key` into `slot`, resolving a repeated
+    /// key by [`DecodeOptions::duplicate_keys`].
+    pub fn parse_field<'t, T>(&mut self, key: u16, ty: u8, slot: &mut Option<T>) -> Result<()>
+    where
+        T: FieldDecoder<'t>,
+        'de: 't,
+    {
+        let policy = self.options.duplicate_keys;
+        if slot.is_some() {
+            match policy {
+                DuplicateKeys::Reject => return Err(self.duplicate(key)),
+                DuplicateKeys::FirstWins => {
+                    return self.skip(ty).map_err(|err| self.at(Segment::Key(key), err));
+                }
+                DuplicateKeys::LastWins | DuplicateKeys::MergeLists => {}
+            }
+        }
+        let value = T::decode_field(self, ty).map_err(|err| self.at(Segment::Key(key), err))?;
+        match slot {
+            Some(existing) if policy == DuplicateKeys::MergeLists => existing.merge(value),
+            _ => *slot = Some(value),
+        }
+        Ok(())
+    }
+
+    /// Takes the value decoded for struct field `key`, which only an `Option`
+    /// field may go without.
+    pub fn take_field<'t, T>(&mut self, key: u16, slot: Option<T>) -> Result<T>
+    where
+        T: FieldDecoder<'t>,
+        'de: 't,
+    {
+        match slot {
+            Some(value) => Ok(value),
+            None => T::absent().map_err(|err| self.at(Segment::Key(key), err)),
+        }
+    }
+
+    /// Decodes a struct field of type `ty` with `f`, which reads the fields of
+    /// the nested struct.
+    pub fn decode_struct<T>(
+        &mut self,
+        ty: u8,
+        f: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if ty != 9 {
+            return self.mismatch(ty);
+        }
+        let skipped_keys = std::mem::take(&mut self.skipped_keys);
+        let result = self.nested(f);
+        self.skipped_keys = skipped_keys;
+        result
+    }
+
+    /// Decodes a list field of type `ty`, handing each element's type to `f`.
+    /// Booleans are read up front and handed over as the field types `0` and `1`.
+    fn decode_list<T>(
+        &mut self,
+        ty: u8,
+        mut f: impl FnMut(&mut Self, u8) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        if ty != 8 {
+            return self.mismatch(ty);
+        }
+        self.nested(|p| {
+            let (len, ty) = p.list_header()?;
+            match ty {
+                0 | 1 => p.collect(len, |p| {
+                    let ty = p.bool()? as u8;
+                    f(p, ty)
+                }),
+                2..=9 => p.collect(len, |p| f(p, ty)),
+                code => Err(ErrorKind::UnknownType { code }.into()),
+            }
+        })
+    }
+
+    /// Fails on a value of type `ty`, which doesn't decode as `T`, parsing it
+    /// first so the error can say what was found.
+    fn mismatch<T>(&mut self, ty: u8) -> Result<T> {
+        let value = self.value(ty)?;
+        Err(value.invalid_type(type_name::<T>()))
+    }
+}
+
+impl<'de> Parser<'_, 'de> {
+    fn raw_str(&mut self) -> Result<&'de str> {
+        let bytes = self.raw_bytes()?;
+        let result = str::from_utf8(bytes);
+        self.utf8(bytes.len(), result)
+    }
+
+    fn raw_bytes(&mut self) -> Result<&'de [u8]> {
+        let len = self.len()?;
+        self.check_bytes(len)?;
+        utils::read_bytes(self.reader, len)
+    }
+
+    /// Validates a list and returns a view of its elements without decoding them.
+    fn list_ref(&mut self) -> Result<ListRef<'de>> {
+        let (len, ty) = self.list_header()?;
+        let start: &'de [u8] = self.reader;
+        self.skip_elements(len, ty)?;
+        let items = &start[..start.len() - self.reader.len()];
+        Ok(ListRef::new(ty, len, items, self.options, self.depth))
+    }
+
+    /// Indexes the remaining fields of the current struct, validating their values
+    /// without decoding them. Encoded lists can't be merged, so repeated keys are
+    /// rejected under [`DuplicateKeys::MergeLists`].
+    fn index(&mut self) -> Result<EntriesRef<'de>> {
+        let mut fields = Vec::new();
+        while let Some((key, ty)) = self.next_field()? {
+            let start: &'de [u8] = self.reader;
+            self.skip(ty)
+                .map_err(|err| self.at(Segment::Key(key), err))?;
+            fields.push((key, (ty, &start[..start.len() - self.reader.len()])));
+        }
+        entries::resolve_duplicates(&mut fields, self.options.duplicate_keys, |_, _| false)
+            .map_err(|key| self.duplicate(key))?;
+        Ok(EntriesRef::new(fields, self.options, self.depth))
+    }
+
+    /// Decodes a value of type `ty`, leaving lists and structs as views.
+    pub(crate) fn value_ref(&mut self, ty: u8) -> Result<ValueRef<'de>> {
+        match ty {
+            0 => Ok(ValueRef::Bool(false)),
+            1 => Ok(ValueRef::Bool(true)),
+            2 => self.f32().map(ValueRef::F32),
+            3 => self.f64().map(ValueRef::F64),
+            4 => self.int().map(ValueRef::Int),
+            5 => self.unsigned().map(ValueRef::UInt),
+            6 => self.raw_str().map(ValueRef::Str),
+            7 => self.raw_bytes().map(ValueRef::Bytes),
+            8 => self.nested(Self::list_ref).map(ValueRef::List),
+            9 => self.nested(Self::index).map(ValueRef::Struct),
+            11 => {
+                let tag = self.unsigned()?;
+                let data = self.raw_bytes()?;
+                Ok(ValueRef::Ext { tag, data })
+            }
+            code => Err(ErrorKind::UnknownType { code }.into()),
+        }
+    }
+
+    /// Decodes a list element of type `ty`.
+    pub(crate) fn element_ref(&mut self, ty: u8) -> Result<ValueRef<'de>> {
+        match ty {
+            0 | 1 => self.bool().map(ValueRef::Bool),
+            _ => self.value_ref(ty),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// Decodes a struct field straight from the parser, without building a
+/// [`Value`] first. List elements are decoded the same way, with a boolean
+/// element handed over as the field type `0` or `1`. The parsed data may
+/// outlive `'de`, as it does when a struct borrows for less than its input.
+pub trait FieldDecoder<'de>: Sized {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self>;
+
+    /// The value of a field the message leaves out.
+    fn absent() -> Result<Self> {
+        Err(Error::conversion(format!(
+            "expected `{}`, found `None`",
+            type_name::<Self>()
+        )))
+    }
+
+    /// Combines the values of a repeated field under [`DuplicateKeys::MergeLists`],
+    /// where anything but a list takes the later value.
+    fn merge(&mut self, later: Self) {
+        *self = later;
+    }
+}
+
+/// A list element type. `u8` isn't one, as `Vec<u8>` is decoded from bytes.
+trait Item<'de>: FieldDecoder<'de> {}
+
+impl<'de, T: FieldDecoder<'de>> FieldDecoder<'de> for Option<T> {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        T::decode_field(p, ty).map(Some)
+    }
+
+    fn absent() -> Result<Self> {
+        Ok(None)
+    }
+
+    fn merge(&mut self, later: Self) {
+        match (self, later) {
+            (Some(value), Some(later)) => value.merge(later),
+            (this, later) => *this = later,
+        }
+    }
+}
+
+impl<'de> FieldDecoder<'de> for bool {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+macro_rules! decode_number {
+    [$($ty:ty: $code:literal => $read:ident)*] => [$(
+        impl<'de> FieldDecoder<'de> for $ty {
+            fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+                match ty {
+                    $code => Ok(<$ty>::try_from(p.$read()?)?),
+                    _ => p.mismatch(ty),
+                }
+            }
+        }
+    )*];
+}
+
+decode_number! {
+    f32: 2 => f32
+    f64: 3 => f64
+
+    i8: 4 => int
+    i16: 4 => int
+    i32: 4 => int
+    i64: 4 => int
+
+    u8: 5 => unsigned
+    u16: 5 => unsigned
+    u32: 5 => unsigned
+    u64: 5 => unsigned
+}
+
+impl<'de> FieldDecoder<'de> for String {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            6 => p.str().map(Cow::into_owned),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+impl<'de> FieldDecoder<'de> for &'de str {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            6 => borrowed(&p.str()?, type_name::<Self>()),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+impl<'de> FieldDecoder<'de> for &'de [u8] {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            7 => borrowed(&p.bytes()?, type_name::<Self>()),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+impl<'de> FieldDecoder<'de> for Vec<u8> {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            7 => p.bytes().map(Cow::into_owned),
+            // Like any `Vec`, from a list with no elements of whatever type.
+            8 => p.decode_list(ty, |p, ty| p.mismatch::<u8>(ty)),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+impl<'de> FieldDecoder<'de> for PathBuf {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            6 => p.str().map(|path| PathBuf::from(path.into_owned())),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+impl<'de, T: Item<'de>> FieldDecoder<'de> for Vec<T> {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        p.decode_list(ty, T::decode_field)
+    }
+
+    fn merge(&mut self, later: Self) {
+        self.extend(later);
+    }
+}
+
+macro_rules! item {
+    [$($ty:ty)*] => [$(impl<'de> Item<'de> for $ty {})*];
+}
+
+item! {
+    bool f32 f64 i8 i16 i32 i64 u16 u32 u64
+    String &'de str &'de [u8] Vec<u8>
+}
+
+impl<'de, T: Item<'de>> Item<'de> for Vec<T> {}
+
+impl<'de, T: Decoder<'de> + FieldDecoder<'de>> Item<'de> for T {}
+
+/// Types carried as bytes, which their [`ConvertFrom`] implementation checks.
+macro_rules! decode_from_bytes {
+    [$($ty:ty)*] => [$(
+        impl<'de> FieldDecoder<'de> for $ty {
+            fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+                match ty {
+                    7 => Self::convert_from(&Value::Bytes(p.bytes()?)),
+                    _ => p.mismatch(ty),
                 }
-                code => Err(errors::UnknownType { code }.into()),
-            }?;
-            entries.insert(key.try_into()?, value);
+            }
+        }
+    )*];
+}
+
+decode_from_bytes! { Ipv4Addr Ipv6Addr IpAddr SocketAddrV4 SocketAddrV6 SocketAddr }
+
+impl<'de, T: Extension> FieldDecoder<'de> for Ext<T> {
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        match ty {
+            11 => Self::convert_from(&p.ext()?),
+            _ => p.mismatch(ty),
+        }
+    }
+}
+
+macro_rules! decode_non_zero {
+    [$($ty:ty)*] => [$(
+        impl<'de> FieldDecoder<'de> for NonZero<$ty> {
+            fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+                NonZero::new(<$ty>::decode_field(p, ty)?).ok_or_else(|| {
+                    Error::conversion(format!("expected `{}`, found `0`", type_name::<Self>()))
+                })
+            }
         }
+    )*];
+}
+
+decode_non_zero! { u8 u16 u32 u64 i8 i16 i32 i64 }
+
+/// Custom field types go through a [`Value`], as they're encoded from one.
+impl<'de, T> FieldDecoder<'de> for T
+where
+    T: IntoValue<'de> + for<'v> ConvertFrom<&'v Value<'de>>,
+{
+    fn decode_field<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>, ty: u8) -> Result<Self> {
+        T::convert_from(&p.value(ty)?)
+    }
+}
+
+/// Checks that `bytes` starts with a well-formed message, including UTF-8 of
+/// strings and the nesting of lists and structs, without building any values.
+/// Returns the length of the message; anything after it is left unchecked.
+pub fn validate(bytes: &[u8]) -> Result<usize> {
+    validate_with(bytes, &DecodeOptions::default())
+}
+
+pub fn validate_with(mut bytes: &[u8], options: &DecodeOptions) -> Result<usize> {
+    let mut parser = Parser::new(&mut bytes, options);
+    match parser.skip_struct() {
+        Ok(()) => Ok(parser.consumed()),
+        Err(err) => Err(parser.locate(err)),
+    }
+}
+
+/// Advances `reader` past a single value of type `ty`, e.g. the payload that
+/// follows a field header, validating it like [`validate`] does.
+/// Returns the number of bytes skipped.
+pub fn skip_value(reader: &mut &[u8], ty: u8) -> Result<usize> {
+    let options = DecodeOptions::default();
+    let mut parser = Parser::new(reader, &options);
+    match parser.skip(ty) {
+        Ok(()) => Ok(parser.consumed()),
+        Err(err) => Err(parser.locate(err)),
+    }
+}
+
+pub(crate) fn ensure_consumed(rest: &[u8]) -> Result<()> {
+    match rest.len() {
+        0 => Ok(()),
+        count => Err(ErrorKind::TrailingBytes { count }.into()),
+    }
+}
+
+pub(crate) fn limit_exceeded(limit: &'static str, max: usize) -> Error {
+    ErrorKind::LimitExceeded { limit, max }.into()
+}
+
+/// Lengths are encoded as `u32` on the wire.
+pub(crate) fn checked_len(len: u64) -> Result<usize> {
+    match u32::try_from(len) {
+        Ok(len) => Ok(len as usize),
+        Err(_) => Err(ErrorKind::InvalidLength { len }.into()),
+    }
+}
+
+impl<'de> EntriesRef<'de> {
+    /// Indexes the fields of a message, checking it is well-formed like
+    /// [`validate`] does but decoding none of its values.
+    pub fn parse(reader: &mut &'de [u8]) -> Result<Self> {
+        Self::parse_with(reader, &DecodeOptions::default())
+    }
+
+    pub fn parse_with(reader: &mut &'de [u8], options: &DecodeOptions) -> Result<Self> {
+        let mut parser = Parser::new(reader, options);
+        parser.index().map_err(|err| parser.locate(err))
+    }
+}
+
+impl Entries<'static> {
+    /// Reads exactly one message from `reader`, leaving it positioned right after
+    /// the struct end marker. Headers are read a byte at a time, so wrap unbuffered
+    /// sources such as sockets in a [`std::io::BufReader`].
+    pub fn read_from(reader: impl Read) -> Result<Self> {
+        Self::read_from_with(reader, &DecodeOptions::default())
+    }
+
+    pub fn read_from_with(reader: impl Read, options: &DecodeOptions) -> Result<Self> {
+        Self::read_from_filtered_with(reader, options, |_| true)
+    }
+
+    /// Like [`Entries::read_from_with`], keeping only the fields whose key passes `keep`.
+    pub fn read_from_filtered_with(
+        reader: impl Read,
+        options: &DecodeOptions,
+        keep: impl FnMut(u16) -> bool,
+    ) -> Result<Self> {
+        let mut parser = Parser::over(reader::Stream::new(reader, options), options);
+        parser.entries_where(keep).map_err(|err| parser.locate(err))
+    }
+}
+
+impl<'de> Entries<'de> {
+    pub fn parse(reader: &mut &'de [u8]) -> Result<Self> {
+        Self::parse_with(reader, &DecodeOptions::default())
+    }
+
+    pub fn parse_with(reader: &mut &'de [u8], options: &DecodeOptions) -> Result<Self> {
+        let mut parser = Parser::new(reader, options);
+        parser.entries().map_err(|err| parser.locate(err))
+    }
+
+    /// Parses only the top-level fields whose key passes `keep`, e.g.
+    /// `|key| [1, 4].contains(&key)`. Other fields are validated and skipped
+    /// without being materialized.
+    pub fn parse_filtered(reader: &mut &'de [u8], keep: impl FnMut(u16) -> bool) -> Result<Self> {
+        Self::parse_filtered_with(reader, &DecodeOptions::default(), keep)
+    }
+
+    pub fn parse_filtered_with(
+        reader: &mut &'de [u8],
+        options: &DecodeOptions,
+        keep: impl FnMut(u16) -> bool,
+    ) -> Result<Self> {
+        let mut parser = Parser::new(reader, options);
+        parser.entries_where(keep).map_err(|err| parser.locate(err))
+    }
+
+    /// Parses a message that must span all of `bytes`.
+    pub fn from_slice_exact(bytes: &'de [u8]) -> Result<Self> {
+        Self::from_slice_exact_with(bytes, &DecodeOptions::default())
+    }
+
+    pub fn from_slice_exact_with(mut bytes: &'de [u8], options: &DecodeOptions) -> Result<Self> {
+        let entries = Self::parse_with(&mut bytes, options)?;
+        ensure_consumed(bytes)?;
         Ok(entries)
     }
 }
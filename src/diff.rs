@@ -0,0 +1,281 @@
+use crate::{Entries, List, Path, Printer, Segment, Total, Value};
+use std::fmt;
+
+/// One difference between two messages, at the path where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'de> {
+    /// A field or list element present only in the new message.
+    Added { path: Path, value: Value<'de> },
+    /// A field or list element present only in the old message.
+    Removed { path: Path, value: Value<'de> },
+    /// A value that differs in type, or is a scalar that differs.
+    Changed {
+        path: Path,
+        old: Value<'de>,
+        new: Value<'de>,
+    },
+}
+
+impl Change<'_> {
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::Added { path, .. } | Change::Removed { path, .. } => path,
+            Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// The differences between two messages, returned by [`Entries::diff`] and
+/// [`Value::diff`].
+///
+/// `Display` writes one change per line, such as `~ 1.[2]: 1u -> 2u`, `+ 3: "new"`
+/// or `- 4: {1: true}`, with values written as by [`Printer`]. A precision such
+/// as `{:.16}` sets [`Printer::max_bytes`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diff<'de>(Vec<Change<'de>>);
+
+impl<'de> Diff<'de> {
+    /// The changes, in the order of the fields and elements of the old message,
+    /// followed by those only in the new one.
+    pub fn changes(&self) -> &[Change<'de>] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl<'de> IntoIterator for Diff<'de> {
+    type Item = Change<'de>;
+    type IntoIter = std::vec::IntoIter<Change<'de>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, 'de> IntoIterator for &'a Diff<'de> {
+    type Item = &'a Change<'de>;
+    type IntoIter = std::slice::Iter<'a, Change<'de>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'de> Value<'de> {
+    /// Compares `self` against `new`, descending into structs by key and into
+    /// lists of the same type by index.
+    ///
+    /// Floats are compared by bit pattern, as by [`Total`], so `NaN` isn't
+    /// reported as a change. A repeated key is compared by its first value.
+    pub fn diff(&self, new: &Value<'de>) -> Diff<'de> {
+        let mut diff = Differ {
+            path: Path::new(),
+            changes: Vec::new(),
+        };
+        diff.value(self, new);
+        Diff(diff.changes)
+    }
+}
+
+impl<'de> Entries<'de> {
+    /// See [`Value::diff`].
+    pub fn diff(&self, new: &Entries<'de>) -> Diff<'de> {
+        let mut diff = Differ {
+            path: Path::new(),
+            changes: Vec::new(),
+        };
+        diff.entries(self, new);
+        Diff(diff.changes)
+    }
+}
+
+struct Differ<'de> {
+    path: Path,
+    changes: Vec<Change<'de>>,
+}
+
+impl<'de> Differ<'de> {
+    fn value(&mut self, old: &Value<'de>, new: &Value<'de>) {
+        match (old, new) {
+            (Value::Struct(old), Value::Struct(new)) => self.entries(old, new),
+            (Value::List(old), Value::List(new)) if same_type(old, new) => self.list(old, new),
+            (old, new) if Total(old) == Total(new) => {}
+            (old, new) => self.changed(old.clone(), new.clone()),
+        }
+    }
+
+    fn entries(&mut self, old: &Entries<'de>, new: &Entries<'de>) {
+        let mut seen = Vec::new();
+        for (key, value) in old {
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            match new.get(key) {
+                Some(new) => self.at(Segment::Key(key), |diff| diff.value(value, new)),
+                None => self.removed(Segment::Key(key), value.clone()),
+            }
+        }
+        for (key, value) in new {
+            if !seen.contains(&key) {
+                seen.push(key);
+                self.added(Segment::Key(key), value.clone());
+            }
+        }
+    }
+
+    fn list(&mut self, old: &List<'de>, new: &List<'de>) {
+        match (old, new) {
+            (List::List(old), List::List(new)) => {
+                self.elements(old, new, Value::List, |diff, old, new| {
+                    match same_type(old, new) {
+                        true => diff.list(old, new),
+                        false => diff.changed(Value::List(old.clone()), Value::List(new.clone())),
+                    }
+                })
+            }
+            (List::Struct(old), List::Struct(new)) => {
+                self.elements(old, new, Value::Struct, |diff, old, new| {
+                    diff.entries(old, new)
+                })
+            }
+            _ => {
+                let (old_len, new_len) = (len(old), len(new));
+                for index in 0..old_len.max(new_len) {
+                    let segment = Segment::Index(index);
+                    match (index < old_len, index < new_len) {
+                        (true, true) => {
+                            let (old, new) = (element(old, index), element(new, index));
+                            self.at(segment, |diff| diff.value(&old, &new));
+                        }
+                        (true, false) => self.removed(segment, element(old, index)),
+                        _ => self.added(segment, element(new, index)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compares lists of containers index-wise with `compare`; elements only
+    /// in one of them are reported as wrapped by `value`.
+    fn elements<T: Clone>(
+        &mut self,
+        old: &[T],
+        new: &[T],
+        value: fn(T) -> Value<'de>,
+        compare: impl Fn(&mut Self, &T, &T),
+    ) {
+        for (index, (old, new)) in old.iter().zip(new).enumerate() {
+            self.at(Segment::Index(index), |diff| compare(diff, old, new));
+        }
+        for (index, old) in old.iter().enumerate().skip(new.len()) {
+            self.removed(Segment::Index(index), value(old.clone()));
+        }
+        for (index, new) in new.iter().enumerate().skip(old.len()) {
+            self.added(Segment::Index(index), value(new.clone()));
+        }
+    }
+
+    fn at(&mut self, segment: Segment, f: impl FnOnce(&mut Self)) {
+        self.path.push(segment);
+        f(self);
+        self.path.pop();
+    }
+
+    fn changed(&mut self, old: Value<'de>, new: Value<'de>) {
+        let path = self.path.clone();
+        self.changes.push(Change::Changed { path, old, new });
+    }
+
+    fn added(&mut self, segment: Segment, value: Value<'de>) {
+        self.at(segment, |diff| {
+            let path = diff.path.clone();
+            diff.changes.push(Change::Added { path, value });
+        });
+    }
+
+    fn removed(&mut self, segment: Segment, value: Value<'de>) {
+        self.at(segment, |diff| {
+            let path = diff.path.clone();
+            diff.changes.push(Change::Removed { path, value });
+        });
+    }
+}
+
+fn same_type(a: &List, b: &List) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+fn len(list: &List) -> usize {
+    match list {
+        List::Bool(items) => items.len(),
+        List::F32(items) => items.len(),
+        List::F64(items) => items.len(),
+        List::Int(items) => items.len(),
+        List::UInt(items) => items.len(),
+        List::Str(items) => items.len(),
+        List::Bytes(items) => items.len(),
+        List::List(items) => items.len(),
+        List::Struct(items) => items.len(),
+    }
+}
+
+/// The `index`th element of `list`, which must be in bounds.
+fn element<'de>(list: &List<'de>, index: usize) -> Value<'de> {
+    match list {
+        List::Bool(items) => Value::Bool(items[index]),
+        List::F32(items) => Value::F32(items[index]),
+        List::F64(items) => Value::F64(items[index]),
+        List::Int(items) => Value::Int(items[index]),
+        List::UInt(items) => Value::UInt(items[index]),
+        List::Str(items) => Value::Str(items[index].clone()),
+        List::Bytes(items) => Value::Bytes(items[index].clone()),
+        List::List(items) => Value::List(items[index].clone()),
+        List::Struct(items) => Value::Struct(items[index].clone()),
+    }
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let printer = Printer {
+            max_bytes: f.precision(),
+            ..Printer::default()
+        };
+        let (sign, path) = match self {
+            Change::Added { path, .. } => ('+', path),
+            Change::Removed { path, .. } => ('-', path),
+            Change::Changed { path, .. } => ('~', path),
+        };
+        write!(f, "{sign} ")?;
+        if !path.is_empty() {
+            write!(f, "{path}: ")?;
+        }
+        match self {
+            Change::Added { value, .. } | Change::Removed { value, .. } => {
+                write!(f, "{}", printer.display(value))
+            }
+            Change::Changed { old, new, .. } => {
+                write!(f, "{} -> {}", printer.display(old), printer.display(new))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, change) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            change.fmt(f)?;
+        }
+        Ok(())
+    }
+}
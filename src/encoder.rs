@@ -1,5 +1,8 @@
 use super::*;
 use std::io::Result;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
 use varint::*;
 
 pub trait FieldEncoder {
@@ -125,6 +128,36 @@ impl Item for &[u8] {
     }
 }
 
+impl Item for String {
+    fn ty() -> u8 {
+        6
+    }
+
+    fn encode(&self, writer: &mut (impl Write + ?Sized)) -> Result<()> {
+        Item::encode(&self.as_str(), writer)
+    }
+}
+
+impl Item for Cow<'_, str> {
+    fn ty() -> u8 {
+        6
+    }
+
+    fn encode(&self, writer: &mut (impl Write + ?Sized)) -> Result<()> {
+        Item::encode(&&**self, writer)
+    }
+}
+
+impl Item for Cow<'_, [u8]> {
+    fn ty() -> u8 {
+        7
+    }
+
+    fn encode(&self, writer: &mut (impl Write + ?Sized)) -> Result<()> {
+        Item::encode(&&**self, writer)
+    }
+}
+
 impl Item for List<'_> {
     fn ty() -> u8 {
         8
@@ -163,19 +196,23 @@ impl<T: Item> Item for Vec<T> {
     }
 }
 
-impl Item for Entries<'_> {
-    fn ty() -> u8 {
-        9
-    }
-
+impl Encoder for Entries<'_> {
     fn encode(&self, writer: &mut (impl Write + ?Sized)) -> Result<()> {
         for (key, value) in self.iter() {
-            Value::encode(value, writer, *key)?;
+            Value::encode(value, writer, key)?;
         }
         writer.write_all(&[10])
     }
 }
 
+impl Entries<'_> {
+    /// Encodes the struct. Defined here as well as through [`Encoder`] so that
+    /// `entries.encode(writer)` isn't ambiguous with [`FieldEncoder`] in scope.
+    pub fn encode(&self, writer: &mut (impl Write + ?Sized)) -> Result<()> {
+        Encoder::encode(self, writer)
+    }
+}
+
 impl<T: Encoder> Item for T {
     fn ty() -> u8 {
         9
@@ -296,14 +333,27 @@ impl FieldEncoder for Value<'_> {
             Value::F64(val) => FieldEncoder::encode(val, writer, id),
             Value::Int(val) => FieldEncoder::encode(val, writer, id),
             Value::UInt(val) => FieldEncoder::encode(val, writer, id),
-            Value::Str(val) => FieldEncoder::encode(*val, writer, id),
-            Value::Bytes(val) => FieldEncoder::encode(*val, writer, id),
+            Value::Str(val) => FieldEncoder::encode(&**val, writer, id),
+            Value::Bytes(val) => FieldEncoder::encode(&**val, writer, id),
             Value::List(list) => FieldEncoder::encode(list, writer, id),
             Value::Struct(entries) => FieldEncoder::encode(entries, writer, id),
+            Value::Ext { tag, data } => encode_ext(writer, id, *tag, data),
         }
     }
 }
 
+fn encode_ext(writer: &mut (impl Write + ?Sized), id: u16, tag: u64, data: &[u8]) -> Result<()> {
+    encode_header(writer, id.into(), 11)?;
+    encode_unsign(writer, tag)?;
+    Item::encode(&data, writer)
+}
+
+impl<T: Extension> FieldEncoder for Ext<T> {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        encode_ext(writer, id, T::TAG, &self.0.encode_ext())
+    }
+}
+
 pub fn encode_struct_field<T: Encoder>(
     this: &T,
     writer: &mut (impl Write + ?Sized),
@@ -319,3 +369,88 @@ impl<T: Item> FieldEncoder for Vec<T> {
         Item::encode(self, writer)
     }
 }
+
+// ----------------------------------------------------------------------------
+
+impl FieldEncoder for Ipv4Addr {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        FieldEncoder::encode(&self.octets()[..], writer, id)
+    }
+}
+
+impl FieldEncoder for Ipv6Addr {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        FieldEncoder::encode(&self.octets()[..], writer, id)
+    }
+}
+
+impl FieldEncoder for IpAddr {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        match self {
+            IpAddr::V4(ip) => FieldEncoder::encode(ip, writer, id),
+            IpAddr::V6(ip) => FieldEncoder::encode(ip, writer, id),
+        }
+    }
+}
+
+// Socket addresses are encoded as the IP octets followed by the port in big-endian.
+// An IPv6 address with a flow label or scope id appends both, also big-endian.
+impl FieldEncoder for SocketAddrV4 {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        let mut buf = [0; 6];
+        buf[..4].copy_from_slice(&self.ip().octets());
+        buf[4..].copy_from_slice(&self.port().to_be_bytes());
+        FieldEncoder::encode(&buf[..], writer, id)
+    }
+}
+
+impl FieldEncoder for SocketAddrV6 {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        let mut buf = [0; 26];
+        buf[..16].copy_from_slice(&self.ip().octets());
+        buf[16..18].copy_from_slice(&self.port().to_be_bytes());
+        buf[18..22].copy_from_slice(&self.flowinfo().to_be_bytes());
+        buf[22..].copy_from_slice(&self.scope_id().to_be_bytes());
+        let len = match (self.flowinfo(), self.scope_id()) {
+            (0, 0) => 18,
+            _ => 26,
+        };
+        FieldEncoder::encode(&buf[..len], writer, id)
+    }
+}
+
+impl FieldEncoder for SocketAddr {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        match self {
+            SocketAddr::V4(addr) => FieldEncoder::encode(addr, writer, id),
+            SocketAddr::V6(addr) => FieldEncoder::encode(addr, writer, id),
+        }
+    }
+}
+
+impl FieldEncoder for Path {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        let path = self
+            .to_str()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "path is not valid UTF-8"))?;
+        FieldEncoder::encode(path, writer, id)
+    }
+}
+
+impl FieldEncoder for PathBuf {
+    fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+        FieldEncoder::encode(self.as_path(), writer, id)
+    }
+}
+
+macro_rules! impl_non_zero {
+    [$($ty:ty)*] => [$(
+        impl FieldEncoder for NonZero<$ty> {
+            fn encode(&self, writer: &mut (impl Write + ?Sized), id: u16) -> Result<()> {
+                FieldEncoder::encode(&self.get(), writer, id)
+            }
+        }
+    )*];
+}
+
+impl_non_zero! { u8 u16 u32 u64 i8 i16 i32 i64 }
@@ -1,46 +1,401 @@
-use crate::{Value, convert::ConvertFrom, errors};
+use crate::{
+    DuplicateKeys, List, Value,
+    convert::{self, ConvertFrom},
+    errors,
+};
+use std::collections::{HashMap, hash_map};
+use std::sync::OnceLock;
 
+/// Below this many fields a linear scan beats building the index.
+const INDEX_THRESHOLD: usize = 16;
+
+/// Fields of a struct, kept in insertion order.
 #[derive(Clone, Default)]
-pub struct Entries<'de>(Vec<(u16, Value<'de>)>);
+pub struct Entries<'de> {
+    entries: Vec<(u16, Value<'de>)>,
+    // `(key, position)` of the first occurrence of each key, sorted by key. Built
+    // on the first lookup of a large struct and kept up to date as fields are
+    // appended; dropped when fields are removed.
+    index: OnceLock<Vec<(u16, usize)>>,
+}
 
 impl<'de> Entries<'de> {
     #[inline]
     pub fn new() -> Self {
-        Self(Vec::with_capacity(8))
+        Self {
+            entries: Vec::with_capacity(8),
+            index: OnceLock::new(),
+        }
     }
 
+    /// Returns the first value stored under `k`.
     pub fn get(&self, k: u16) -> Option<&Value<'de>> {
-        self.0
-            .iter()
-            .find_map(|(key, value)| (*key == k).then_some(value))
+        self.position(k).map(|pos| &self.entries[pos].1)
+    }
+
+    pub fn get_mut(&mut self, k: u16) -> Option<&mut Value<'de>> {
+        self.position(k).map(|pos| &mut self.entries[pos].1)
+    }
+
+    fn position(&self, k: u16) -> Option<usize> {
+        if self.entries.len() < INDEX_THRESHOLD {
+            return self.entries.iter().position(|(key, _)| *key == k);
+        }
+        let index = self.index.get_or_init(|| {
+            let mut index: Vec<_> = self
+                .entries
+                .iter()
+                .enumerate()
+                .map(|(pos, (key, _))| (*key, pos))
+                .collect();
+            // Repeated keys stay ordered by position, so the first occurrence is kept.
+            index.sort_unstable();
+            index.dedup_by_key(|(key, _)| *key);
+            index
+        });
+        let i = index.partition_point(|(key, _)| *key < k);
+        index
+            .get(i)
+            .filter(|(key, _)| *key == k)
+            .map(|(_, pos)| *pos)
+    }
+
+    /// Sets the value of field `key`, returning the previous value if the key
+    /// was already present, like `HashMap::insert`. An existing field keeps its
+    /// position; a new one is appended.
+    pub fn insert(&mut self, key: u16, value: Value<'de>) -> Option<Value<'de>> {
+        match self.position(key) {
+            Some(pos) => Some(std::mem::replace(&mut self.entries[pos].1, value)),
+            None => {
+                self.push(key, value);
+                None
+            }
+        }
+    }
+
+    /// Appends a field even if `key` is already present, as the parser does
+    /// before resolving repeated keys.
+    #[inline]
+    pub(crate) fn push(&mut self, key: u16, value: Value<'de>) {
+        let pos = self.entries.len();
+        self.entries.push((key, value));
+        if let Some(index) = self.index.get_mut() {
+            let i = index.partition_point(|(k, _)| *k < key);
+            if index.get(i).is_none_or(|(k, _)| *k != key) {
+                index.insert(i, (key, pos));
+            }
+        }
+    }
+
+    /// Gets the field `key` for in-place insertion or update.
+    pub fn entry(&mut self, key: u16) -> Entry<'_, 'de> {
+        Entry {
+            position: self.position(key),
+            entries: self,
+            key,
+        }
+    }
+
+    /// Removes every field stored under `k`, returning the first value.
+    pub fn remove(&mut self, k: u16) -> Option<Value<'de>> {
+        let pos = self.position(k)?;
+        self.index.take();
+        let (_, value) = self.entries.remove(pos);
+        self.entries.retain(|(key, _)| *key != k);
+        Some(value)
+    }
+
+    /// Keeps only the fields for which `f` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(u16, &mut Value<'de>) -> bool) {
+        self.index.take();
+        self.entries.retain_mut(|(key, value)| f(*key, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.index.take();
+        self.entries.clear();
     }
 
     #[inline]
-    pub fn insert(&mut self, key: u16, value: Value<'de>) {
-        self.0.push((key, value));
+    pub fn contains_key(&self, k: u16) -> bool {
+        self.position(k).is_some()
+    }
+
+    /// Iterates over the fields in insertion order.
+    pub fn iter(&self) -> Iter<'_, 'de> {
+        Iter(self.entries.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, 'de> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = u16> + ExactSizeIterator + '_ {
+        self.entries.iter().map(|(key, _)| *key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Value<'de>> + ExactSizeIterator {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut Value<'de>> + ExactSizeIterator {
+        self.entries.iter_mut().map(|(_, value)| value)
     }
 
     pub fn get_and_convert<'v, T>(&'v self, k: u16) -> Result<T, errors::ConvertError>
     where
         T: ConvertFrom<Option<&'v Value<'de>>>,
     {
-        T::convert_from(self.get(k)).map_err(|mut err| {
-            err.key = Some(k);
-            err
-        })
+        convert::convert_field(k, self.get(k))
     }
 
     #[inline]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.entries.is_empty()
     }
 
     #[inline]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.entries.len()
+    }
+
+    pub fn into_owned(self) -> Entries<'static> {
+        Entries {
+            entries: self
+                .entries
+                .into_iter()
+                .map(|(key, value)| (key, value.into_owned()))
+                .collect(),
+            index: self.index,
+        }
+    }
+
+    pub fn as_borrowed(&self) -> Entries<'_> {
+        Entries {
+            entries: self
+                .entries
+                .iter()
+                .map(|(key, value)| (*key, value.as_borrowed()))
+                .collect(),
+            index: self.index.clone(),
+        }
+    }
+
+    pub(crate) fn fields(&self) -> &[(u16, Value<'de>)] {
+        &self.entries
+    }
+
+    /// Applies `policy` to repeated keys, returning the key it rejects, if any.
+    pub(crate) fn resolve_duplicates(&mut self, policy: DuplicateKeys) -> Result<(), u16> {
+        self.index.take();
+        resolve_duplicates(&mut self.entries, policy, |value, other| {
+            merge(value, other);
+            true
+        })
+    }
+}
+
+/// A field of [`Entries`] that may not exist yet, returned by [`Entries::entry`].
+pub struct Entry<'a, 'de> {
+    entries: &'a mut Entries<'de>,
+    key: u16,
+    position: Option<usize>,
+}
+
+impl<'a, 'de> Entry<'a, 'de> {
+    #[inline]
+    pub fn key(&self) -> u16 {
+        self.key
+    }
+
+    /// Sets the value of the field, returning the previous one if there was any.
+    pub fn insert(self, value: Value<'de>) -> Option<Value<'de>> {
+        match self.position {
+            Some(pos) => Some(std::mem::replace(&mut self.entries.entries[pos].1, value)),
+            None => {
+                self.entries.push(self.key, value);
+                None
+            }
+        }
     }
 
-    pub(crate) fn iter(&self) -> std::slice::Iter<'_, (u16, Value<'de>)> {
-        self.0.iter()
+    pub fn or_insert(self, default: Value<'de>) -> &'a mut Value<'de> {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> Value<'de>) -> &'a mut Value<'de> {
+        let pos = match self.position {
+            Some(pos) => pos,
+            None => {
+                self.entries.push(self.key, default());
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries.entries[pos].1
+    }
+
+    /// Runs `f` on the value if the field exists.
+    pub fn and_modify(self, f: impl FnOnce(&mut Value<'de>)) -> Self {
+        if let Some(pos) = self.position {
+            f(&mut self.entries.entries[pos].1);
+        }
+        self
+    }
+}
+
+pub struct Iter<'a, 'de>(std::slice::Iter<'a, (u16, Value<'de>)>);
+
+pub struct IterMut<'a, 'de>(std::slice::IterMut<'a, (u16, Value<'de>)>);
+
+pub struct IntoIter<'de>(std::vec::IntoIter<(u16, Value<'de>)>);
+
+macro_rules! impl_iter {
+    [$($name:ident<$($lt:lifetime),*> => $item:ty, |$field:pat_param| $map:expr;)*] => {$(
+        impl<$($lt),*> Iterator for $name<$($lt),*> {
+            type Item = $item;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next().map(|$field| $map)
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl<$($lt),*> DoubleEndedIterator for $name<$($lt),*> {
+            #[inline]
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.0.next_back().map(|$field| $map)
+            }
+        }
+
+        impl<$($lt),*> ExactSizeIterator for $name<$($lt),*> {}
+    )*};
+}
+
+impl_iter! {
+    Iter<'a, 'de> => (u16, &'a Value<'de>), |(key, value)| (*key, value);
+    IterMut<'a, 'de> => (u16, &'a mut Value<'de>), |(key, value)| (*key, value);
+    IntoIter<'de> => (u16, Value<'de>), |field| field;
+}
+
+/// Fields are compared in order; see [`Entries::eq_unordered`] otherwise.
+impl PartialEq for Entries<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<'a, 'de> IntoIterator for &'a Entries<'de> {
+    type Item = (u16, &'a Value<'de>);
+    type IntoIter = Iter<'a, 'de>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, 'de> IntoIterator for &'a mut Entries<'de> {
+    type Item = (u16, &'a mut Value<'de>);
+    type IntoIter = IterMut<'a, 'de>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<'de> IntoIterator for Entries<'de> {
+    type Item = (u16, Value<'de>);
+    type IntoIter = IntoIter<'de>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+/// A repeated key keeps the position of its first occurrence and the value of
+/// its last, as with [`Entries::insert`].
+impl<'de> FromIterator<(u16, Value<'de>)> for Entries<'de> {
+    fn from_iter<I: IntoIterator<Item = (u16, Value<'de>)>>(iter: I) -> Self {
+        let mut entries = Entries::new();
+        entries.extend(iter);
+        entries
+    }
+}
+
+impl<'de> Extend<(u16, Value<'de>)> for Entries<'de> {
+    fn extend<I: IntoIterator<Item = (u16, Value<'de>)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+fn has_duplicates<T>(fields: &[(u16, T)]) -> bool {
+    if fields.len() < INDEX_THRESHOLD {
+        return fields
+            .iter()
+            .enumerate()
+            .any(|(i, (key, _))| fields[..i].iter().any(|(k, _)| k == key));
+    }
+    let mut keys: Vec<u16> = fields.iter().map(|(key, _)| *key).collect();
+    keys.sort_unstable();
+    keys.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+/// Collapses repeated keys into the position of their first occurrence.
+/// `merge` combines two values under [`DuplicateKeys::MergeLists`], returning
+/// `false` if they can't be combined.
+pub(crate) fn resolve_duplicates<T>(
+    fields: &mut Vec<(u16, T)>,
+    policy: DuplicateKeys,
+    mut merge: impl FnMut(&mut T, T) -> bool,
+) -> Result<(), u16> {
+    if !has_duplicates(fields) {
+        return Ok(());
+    }
+    let mut positions = HashMap::new();
+    let mut resolved = Vec::with_capacity(fields.len());
+    for (key, value) in fields.drain(..) {
+        let pos = match positions.entry(key) {
+            hash_map::Entry::Occupied(entry) => *entry.get(),
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(resolved.len());
+                resolved.push((key, value));
+                continue;
+            }
+        };
+        let existing = &mut resolved[pos].1;
+        match policy {
+            DuplicateKeys::Reject => return Err(key),
+            DuplicateKeys::FirstWins => {}
+            DuplicateKeys::LastWins => *existing = value,
+            DuplicateKeys::MergeLists => {
+                if !merge(existing, value) {
+                    return Err(key);
+                }
+            }
+        }
+    }
+    *fields = resolved;
+    Ok(())
+}
+
+/// Appends `other` to `value` if both are lists of the same type, otherwise
+/// replaces `value`.
+pub(crate) fn merge<'de>(value: &mut Value<'de>, other: Value<'de>) {
+    macro_rules! extend {
+        [$($name:ident)*] => {
+            match (value, other) {
+                $((Value::List(List::$name(items)), Value::List(List::$name(more))) => items.extend(more),)*
+                (value, other) => *value = other,
+            }
+        };
     }
+    extend![Bool F32 F64 Int UInt Str Bytes List Struct]
 }
@@ -0,0 +1,203 @@
+use crate::{DecodeOptions, Result, Segment, decoder::Parser};
+use std::fmt;
+
+/// Borrowed view of a struct that only indexes where each field is.
+///
+/// Field values are decoded on demand by [`EntriesRef::get`], and lists are
+/// exposed as lazy iterators, so picking a field out of a large message
+/// allocates nothing but the index.
+#[derive(Clone, Default)]
+pub struct EntriesRef<'de> {
+    fields: Vec<(u16, (u8, &'de [u8]))>,
+    // Nested values are decoded with the options and at the depth they were
+    // indexed with.
+    options: DecodeOptions,
+    depth: usize,
+}
+
+/// A field value decoded from an [`EntriesRef`].
+#[derive(Debug, Clone)]
+pub enum ValueRef<'de> {
+    Bool(bool),
+    F32(f32),
+    F64(f64),
+    Int(i64),
+    UInt(u64),
+    Str(&'de str),
+    Bytes(&'de [u8]),
+    List(ListRef<'de>),
+    Struct(EntriesRef<'de>),
+    Ext { tag: u64, data: &'de [u8] },
+}
+
+/// Borrowed view of a list whose elements are decoded while iterating.
+#[derive(Clone, Copy)]
+pub struct ListRef<'de> {
+    ty: u8,
+    len: usize,
+    items: &'de [u8],
+    options: DecodeOptions,
+    depth: usize,
+}
+
+pub struct ListIter<'de> {
+    ty: u8,
+    index: usize,
+    len: usize,
+    reader: &'de [u8],
+    options: DecodeOptions,
+    depth: usize,
+}
+
+/// Runs `f` on a parser over bytes that were already validated while indexing.
+fn decode<'de>(
+    reader: &mut &'de [u8],
+    options: &DecodeOptions,
+    depth: usize,
+    f: impl FnOnce(&mut Parser<'_, 'de>) -> Result<ValueRef<'de>>,
+) -> Result<ValueRef<'de>> {
+    f(&mut Parser::at_depth(reader, options, depth))
+}
+
+impl<'de> EntriesRef<'de> {
+    pub(crate) fn new(
+        fields: Vec<(u16, (u8, &'de [u8]))>,
+        options: &DecodeOptions,
+        depth: usize,
+    ) -> Self {
+        Self {
+            fields,
+            options: *options,
+            depth,
+        }
+    }
+
+    /// Decodes the value of field `key`.
+    pub fn get(&self, key: u16) -> Result<Option<ValueRef<'de>>> {
+        let Some(&(_, (ty, mut raw))) = self.fields.iter().find(|(k, _)| *k == key) else {
+            return Ok(None);
+        };
+        decode(&mut raw, &self.options, self.depth, |p| p.value_ref(ty))
+            .map(Some)
+            .map_err(|err| err.at(Segment::Key(key)))
+    }
+
+    /// The encoded value of field `key`, without its header.
+    pub fn raw(&self, key: u16) -> Option<&'de [u8]> {
+        self.fields
+            .iter()
+            .find_map(|&(k, (_, raw))| (k == key).then_some(raw))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = u16> + '_ {
+        self.fields.iter().map(|(key, _)| *key)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+impl<'de> ListRef<'de> {
+    pub(crate) fn new(
+        ty: u8,
+        len: usize,
+        items: &'de [u8],
+        options: &DecodeOptions,
+        depth: usize,
+    ) -> Self {
+        Self {
+            ty,
+            len,
+            items,
+            options: *options,
+            depth,
+        }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn iter(&self) -> ListIter<'de> {
+        ListIter {
+            ty: self.ty,
+            index: 0,
+            len: self.len,
+            reader: self.items,
+            options: self.options,
+            depth: self.depth,
+        }
+    }
+}
+
+impl<'de> IntoIterator for ListRef<'de> {
+    type Item = Result<ValueRef<'de>>;
+    type IntoIter = ListIter<'de>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'de> Iterator for ListIter<'de> {
+    type Item = Result<ValueRef<'de>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            return None;
+        }
+        let index = self.index;
+        let ty = self.ty;
+        let item = decode(&mut self.reader, &self.options, self.depth, |p| {
+            p.element_ref(ty)
+        });
+        self.index = if item.is_ok() { index + 1 } else { self.len };
+        Some(item.map_err(|err| err.at(Segment::Index(index))))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ListIter<'_> {}
+
+impl fmt::Debug for EntriesRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        for &(key, (ty, mut raw)) in &self.fields {
+            match decode(&mut raw, &self.options, self.depth, |p| p.value_ref(ty)) {
+                Ok(value) => map.entry(&key, &value),
+                Err(err) => map.entry(&key, &err),
+            };
+        }
+        map.finish()
+    }
+}
+
+impl fmt::Debug for ListRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for item in self.iter() {
+            match item {
+                Ok(value) => list.entry(&value),
+                Err(err) => list.entry(&err),
+            };
+        }
+        list.finish()
+    }
+}
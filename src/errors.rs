@@ -1,58 +1,228 @@
-use crate::{List, Value};
-use std::fmt;
+use crate::{List, Path, Segment, Value};
+use std::{error::Error as StdError, fmt, io};
 
-#[derive(Debug)]
-pub struct UnexpectedEof;
-impl std::error::Error for UnexpectedEof {}
-impl fmt::Display for UnexpectedEof {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("unexpected end of file")
+/// Former name of [`Error`], kept for `ConvertFrom` implementations.
+pub type ConvertError = Error;
+
+type Source = Box<dyn StdError + Send + Sync + 'static>;
+
+/// What went wrong while decoding or converting a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input ended in the middle of a value.
+    Eof,
+    /// A variable-length integer doesn't fit in 64 bits.
+    InvalidVarint,
+    /// A header carries a type code that isn't defined.
+    UnknownType { code: u8 },
+    /// A string isn't valid UTF-8.
+    InvalidUtf8,
+    /// A boolean list element is neither `0` nor `1`.
+    InvalidBool { value: u8 },
+    /// A struct key doesn't fit in `u16`.
+    InvalidKey { key: u64 },
+    /// A string, bytes or list length doesn't fit in `u32`.
+    InvalidLength { len: u64 },
+    /// Nesting exceeds [`DecodeOptions::max_depth`](crate::DecodeOptions::max_depth).
+    DepthLimitExceeded { max_depth: usize },
+    /// A [`DecodeOptions`](crate::DecodeOptions) limit, named after its field, was exceeded.
+    LimitExceeded { limit: &'static str, max: usize },
+    /// A struct repeats a key while [`DuplicateKeys::Reject`](crate::DuplicateKeys::Reject) is in effect.
+    DuplicateKey { key: u16 },
+    /// The input continues after the end of the message.
+    TrailingBytes { count: usize },
+    /// A value couldn't be converted to the requested type.
+    Conversion { message: String },
+    /// Reading from an [`io::Read`] source failed.
+    Io,
+    /// Input to [`Value::from_text`] doesn't follow the text format.
+    Syntax { message: String },
+}
+
+/// Error returned by every decoding and conversion entry point.
+///
+/// Parse errors carry the byte offset where parsing failed, and both parse and
+/// conversion errors carry the [`Path`] of the value involved.
+pub struct Error {
+    kind: ErrorKind,
+    offset: Option<usize>,
+    path: Path,
+    source: Option<Source>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            offset: None,
+            path: Path::new(),
+            source: None,
+        }
+    }
+
+    pub fn conversion(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Conversion {
+            message: message.into(),
+        })
+    }
+
+    /// Conversion error caused by `source`, e.g. from a custom `ConvertFrom`
+    /// implementation. The details are left to `source`, so that reporters
+    /// walking the error chain don't print them twice.
+    pub fn custom(source: impl Into<Source>) -> Self {
+        Self::conversion("custom conversion failed").with_source(source)
+    }
+
+    fn with_source(mut self, source: impl Into<Source>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Byte offset in the input where parsing failed.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Location of the value that failed, outermost key first.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Records that the error happened inside `segment`.
+    pub fn at(mut self, segment: Segment) -> Self {
+        self.path.push_front(segment);
+        self
+    }
+
+    /// Places the error at `offset`, inside `path`, which precedes any path
+    /// the error already carries.
+    pub(crate) fn located(mut self, offset: Option<usize>, mut path: Path) -> Self {
+        self.offset = offset;
+        for segment in self.path.segments() {
+            path.push(*segment);
+        }
+        self.path = path;
+        self
+    }
+
+    pub(crate) fn utf8(err: std::str::Utf8Error) -> Self {
+        Self::new(ErrorKind::InvalidUtf8).with_source(err)
+    }
+
+    pub(crate) fn io(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof => Self::new(ErrorKind::Eof),
+            _ => Self::new(ErrorKind::Io).with_source(err),
+        }
     }
 }
 
-#[derive(Debug)]
-pub struct VarIntError;
-impl std::error::Error for VarIntError {}
-impl fmt::Display for VarIntError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("invalid variable-length integer")
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
     }
 }
 
-#[derive(Debug)]
-pub struct ParseError {
-    pub message: Box<str>,
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Self::conversion(message)
+    }
 }
 
-impl std::error::Error for ParseError {}
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self, f)
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Self::conversion(message)
     }
 }
 
-impl ParseError {
-    pub fn new(message: impl Into<Box<str>>) -> Self {
-        Self {
-            message: message.into(),
+impl From<std::num::TryFromIntError> for Error {
+    fn from(err: std::num::TryFromIntError) -> Self {
+        Self::custom(err)
+    }
+}
+
+impl From<std::convert::Infallible> for Error {
+    fn from(err: std::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::Eof => f.write_str("unexpected end of file"),
+            ErrorKind::InvalidVarint => f.write_str("invalid variable-length integer"),
+            ErrorKind::UnknownType { code } => write!(f, "unknown type: {code}"),
+            ErrorKind::InvalidUtf8 => f.write_str("invalid UTF-8 in string"),
+            ErrorKind::InvalidBool { value } => write!(f, "invalid boolean value: `{value}`"),
+            ErrorKind::InvalidKey { key } => write!(f, "key `{key}` is out of range"),
+            ErrorKind::InvalidLength { len } => write!(f, "length `{len}` is out of range"),
+            ErrorKind::DepthLimitExceeded { max_depth } => {
+                write!(f, "nesting exceeds the maximum depth of {max_depth}")
+            }
+            ErrorKind::LimitExceeded { limit, max } => {
+                write!(f, "`{limit}` limit of {max} exceeded")
+            }
+            ErrorKind::DuplicateKey { key } => write!(f, "duplicate key `{key}`"),
+            ErrorKind::TrailingBytes { count } => {
+                write!(f, "{count} trailing bytes after the end of the message")
+            }
+            ErrorKind::Conversion { message } => f.write_str(message),
+            ErrorKind::Io => f.write_str("I/O error"),
+            ErrorKind::Syntax { message } => f.write_str(message),
         }
     }
 }
 
-#[derive(Debug)]
-pub struct UnknownType {
-    pub code: u8,
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (ErrorKind::Conversion { message }, false) = (&self.kind, self.path.is_empty()) {
+            return write!(f, "conversion error for key `{}`: {message}", self.path);
+        }
+        self.kind.fmt(f)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at byte {offset}")?;
+        }
+        if !self.path.is_empty() {
+            write!(f, " (field `{}`)", self.path)?;
+        }
+        Ok(())
+    }
 }
 
-impl std::error::Error for UnknownType {}
-impl fmt::Display for UnknownType {
+impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "unknown type: {}", self.code)
+        let mut obj = f.debug_struct("Error");
+        obj.field("kind", &self.kind);
+        if let Some(offset) = self.offset {
+            obj.field("offset", &offset);
+        }
+        if !self.path.is_empty() {
+            obj.field("path", &self.path);
+        }
+        if let Some(source) = &self.source {
+            obj.field("source", source);
+        }
+        obj.finish()
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match &self.source {
+            Some(source) => Some(&**source),
+            None => None,
+        }
     }
 }
 
 impl List<'_> {
-    fn type_name(&self) -> &str {
+    pub(crate) fn type_name(&self) -> &str {
         match self {
             List::Bool(_) => "[boolean]",
             List::F32(_) => "[f32]",
@@ -66,8 +236,8 @@ impl List<'_> {
         }
     }
 
-    pub(crate) fn invalid_type(&self, expected: &str) -> ConvertError {
-        ConvertError::new(format!(
+    pub(crate) fn invalid_type(&self, expected: &str) -> Error {
+        Error::conversion(format!(
             "expected `{expected}`, found `{}`",
             self.type_name()
         ))
@@ -75,7 +245,7 @@ impl List<'_> {
 }
 
 impl Value<'_> {
-    fn type_name(&self) -> &str {
+    pub(crate) fn type_name(&self) -> &str {
         match self {
             Value::Bool(_) => "boolean",
             Value::F32(_) => "f32",
@@ -85,57 +255,15 @@ impl Value<'_> {
             Value::Str(_) => "string",
             Value::Bytes(_) => "bytes",
             Value::Struct(_) => "struct",
+            Value::Ext { .. } => "extension",
             Value::List(list) => list.type_name(),
         }
     }
 
-    pub(crate) fn invalid_type(&self, expected: &str) -> ConvertError {
-        ConvertError::new(format!(
+    pub(crate) fn invalid_type(&self, expected: &str) -> Error {
+        Error::conversion(format!(
             "expected `{expected}`, found `{}`",
             self.type_name()
         ))
     }
 }
-
-pub struct ConvertError {
-    pub key: Option<u16>,
-    pub error: crate::Error,
-}
-
-impl ConvertError {
-    pub fn new(message: String) -> Self {
-        Self {
-            key: None,
-            error: message.into(),
-        }
-    }
-
-    pub fn from(value: impl Into<crate::Error>) -> Self {
-        Self {
-            key: None,
-            error: value.into(),
-        }
-    }
-}
-
-impl std::error::Error for ConvertError {}
-impl fmt::Display for ConvertError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.key {
-            Some(key) => {
-                write!(f, "conversion error for key `{key}`: {}", self.error)
-            }
-            None => f.write_str(&self.error.to_string()),
-        }
-    }
-}
-impl fmt::Debug for ConvertError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut obj = f.debug_struct("ConvertError");
-        match self.key {
-            Some(key) => obj.field("key", &key).field("message", &self.error),
-            None => obj.field("message", &self.error),
-        }
-        .finish()
-    }
-}
@@ -0,0 +1,71 @@
+use crate::{Value, errors::ConvertError};
+use std::fmt;
+use std::sync::{PoisonError, RwLock};
+
+/// Application-defined type carried on the wire as an extension value:
+/// a tag identifying the type plus an opaque payload.
+///
+/// Use the [`Ext`] wrapper to put an extension type in a derived struct.
+pub trait Extension: Sized {
+    const TAG: u64;
+
+    fn encode_ext(&self) -> Vec<u8>;
+    fn decode_ext(data: &[u8]) -> Result<Self, ConvertError>;
+}
+
+/// Field wrapper that encodes and decodes `T` as an extension value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ext<T>(pub T);
+
+impl<T> From<T> for Ext<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<'de, T: Extension> crate::ConvertFrom<&Value<'de>> for Ext<T> {
+    fn convert_from(value: &Value<'de>) -> Result<Self, ConvertError> {
+        match value {
+            Value::Ext { tag, data } if *tag == T::TAG => T::decode_ext(data).map(Ext),
+            Value::Ext { tag, .. } => Err(ConvertError::conversion(format!(
+                "expected extension tag `{}`, found `{tag}`",
+                T::TAG
+            ))),
+            val => Err(val.invalid_type(std::any::type_name::<T>())),
+        }
+    }
+}
+
+pub(crate) type Render = fn(&[u8], &mut fmt::Formatter<'_>) -> Option<fmt::Result>;
+
+static EXTENSIONS: RwLock<Vec<(u64, Render)>> = RwLock::new(Vec::new());
+
+/// Registers `T` so that printing a [`Value::Ext`] with `T::TAG` renders the
+/// decoded value using its `Debug` implementation, after the tag, e.g.
+/// `#7(Point { x: 1, y: 2 })`.
+pub fn register_extension<T: Extension + fmt::Debug>() {
+    fn render<T: Extension + fmt::Debug>(
+        data: &[u8],
+        f: &mut fmt::Formatter<'_>,
+    ) -> Option<fmt::Result> {
+        let val = T::decode_ext(data).ok()?;
+        Some(write!(f, "#{}(", T::TAG).and_then(|()| {
+            fmt::Debug::fmt(&val, f)?;
+            f.write_str(")")
+        }))
+    }
+    // Each update is a single assignment or push, so a poisoned lock still
+    // guards a consistent list.
+    let mut extensions = EXTENSIONS.write().unwrap_or_else(PoisonError::into_inner);
+    match extensions.iter_mut().find(|(tag, _)| *tag == T::TAG) {
+        Some((_, f)) => *f = render::<T>,
+        None => extensions.push((T::TAG, render::<T>)),
+    }
+}
+
+pub(crate) fn renderer(tag: u64) -> Option<Render> {
+    let extensions = EXTENSIONS.read().unwrap_or_else(PoisonError::into_inner);
+    extensions
+        .iter()
+        .find_map(|(t, f)| (*t == tag).then_some(*f))
+}
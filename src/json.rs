@@ -0,0 +1,314 @@
+//! Transcoding between [`Value`] and JSON.
+//!
+//! Writing JSON:
+//!
+//! - `Int` and `UInt` become integers, written exactly.
+//! - `F32` and `F64` become numbers, each with the shortest representation that
+//!   reads back as the same float; `NaN` and infinities become `null`.
+//! - `Bytes` become base64 strings (standard alphabet, padded).
+//! - Lists become arrays and structs become objects keyed by the decimal key.
+//! - Extension values become `{"tag": 7, "data": "<base64>"}`.
+//!
+//! Reading JSON, which carries no type information beyond the above:
+//!
+//! - Non-negative integers become `UInt`, negative ones `Int`. An array holding
+//!   both becomes a list of `Int`; one that also holds fractions becomes `F64`.
+//! - Any other number becomes `F64`; strings become `Str`.
+//! - Objects whose keys are all `u16` become structs; `{"tag", "data"}` objects
+//!   become extension values.
+//! - An empty array becomes an empty list of `Int`.
+//! - `null` and other objects are rejected.
+
+use crate::{Entries, Error, ErrorKind, List, Result, Segment, Value, utils};
+use serde_json::{Map, Number, Value as Json};
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+/// Writes `value` as JSON without building an intermediate tree, e.g. straight
+/// from the output of [`Entries::parse`].
+pub fn to_writer<T: ToJson + ?Sized>(mut writer: impl Write, value: &T) -> io::Result<()> {
+    value.write_json(&mut writer)
+}
+
+pub fn to_string<T: ToJson + ?Sized>(value: &T) -> String {
+    let mut out = Vec::new();
+    to_writer(&mut out, value).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn to_value<T: ToJson + ?Sized>(value: &T) -> Json {
+    value.to_json()
+}
+
+pub fn from_str(json: &str) -> Result<Value<'static>> {
+    let json = serde_json::from_str(json).map_err(|err| {
+        Error::new(ErrorKind::Syntax {
+            message: err.to_string(),
+        })
+    })?;
+    from_value(json)
+}
+
+pub fn from_value(json: Json) -> Result<Value<'static>> {
+    Ok(match json {
+        Json::Null => return Err(Error::conversion("`null` has no cor equivalent")),
+        Json::Bool(val) => Value::Bool(val),
+        Json::Number(number) => match (number.as_u64(), number.as_i64()) {
+            (Some(val), _) => Value::UInt(val),
+            (None, Some(val)) => Value::Int(val),
+            _ => Value::F64(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Json::String(val) => Value::Str(Cow::Owned(val)),
+        Json::Array(items) => Value::List(list(items)?),
+        Json::Object(map) => object(map)?,
+    })
+}
+
+fn list(items: Vec<Json>) -> Result<List<'static>> {
+    if items.is_empty() {
+        return Ok(List::Int(Vec::new()));
+    }
+    let numbers: Option<Vec<&Number>> = items
+        .iter()
+        .map(|item| match item {
+            Json::Number(number) => Some(number),
+            _ => None,
+        })
+        .collect();
+    if let Some(numbers) = numbers {
+        return Ok(number_list(&numbers));
+    }
+
+    let mut values = items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| from_value(item).map_err(|err| err.at(Segment::Index(i))));
+    let first = values.next().unwrap()?;
+    macro_rules! collect {
+        [$($name:ident)*] => {
+            match first {
+                $(Value::$name(first) => {
+                    let mut items = vec![first];
+                    for value in values {
+                        match value? {
+                            Value::$name(item) => items.push(item),
+                            _ => return Err(mixed()),
+                        }
+                    }
+                    List::$name(items)
+                })*
+                Value::Ext { .. } => {
+                    return Err(Error::conversion("arrays of extension values aren't supported"));
+                }
+                _ => return Err(mixed()),
+            }
+        };
+    }
+    Ok(collect![Bool Str List Struct])
+}
+
+fn mixed() -> Error {
+    Error::conversion("array elements must all have the same type")
+}
+
+fn number_list(numbers: &[&Number]) -> List<'static> {
+    if let Some(items) = numbers.iter().map(|n| n.as_u64()).collect() {
+        return List::UInt(items);
+    }
+    if let Some(items) = numbers.iter().map(|n| n.as_i64()).collect() {
+        return List::Int(items);
+    }
+    List::F64(
+        numbers
+            .iter()
+            .map(|n| n.as_f64().unwrap_or(f64::NAN))
+            .collect(),
+    )
+}
+
+fn object(map: Map<String, Json>) -> Result<Value<'static>> {
+    if let (2, Some(Json::Number(tag)), Some(Json::String(data))) =
+        (map.len(), map.get("tag"), map.get("data"))
+    {
+        let tag = tag
+            .as_u64()
+            .ok_or_else(|| Error::conversion("extension tag must be an unsigned integer"))?;
+        let data = utils::decode_base64(data)
+            .ok_or_else(|| Error::conversion("extension data must be base64"))?;
+        return Ok(Value::Ext {
+            tag,
+            data: Cow::Owned(data),
+        });
+    }
+    let mut entries = Entries::new();
+    for (key, value) in map {
+        let key: u16 = key
+            .parse()
+            .map_err(|_| Error::conversion(format!("object key `{key}` isn't a struct key")))?;
+        let value = from_value(value).map_err(|err| err.at(Segment::Key(key)))?;
+        entries.insert(key, value);
+    }
+    Ok(Value::Struct(entries))
+}
+
+/// Values that can be written as JSON.
+pub trait ToJson {
+    fn write_json(&self, w: &mut dyn Write) -> io::Result<()>;
+    fn to_json(&self) -> Json;
+}
+
+impl ToJson for Value<'_> {
+    fn write_json(&self, w: &mut dyn Write) -> io::Result<()> {
+        match self {
+            Value::Bool(val) => write!(w, "{val}"),
+            Value::F32(val) => write_float(w, val.is_finite(), val),
+            Value::F64(val) => write_float(w, val.is_finite(), val),
+            Value::Int(val) => write!(w, "{val}"),
+            Value::UInt(val) => write!(w, "{val}"),
+            Value::Str(val) => write_str(w, val),
+            Value::Bytes(val) => write_str(w, &base64(val)),
+            Value::List(list) => list.write_json(w),
+            Value::Struct(entries) => entries.write_json(w),
+            Value::Ext { tag, data } => {
+                write!(w, "{{\"tag\":{tag},\"data\":")?;
+                write_str(w, &base64(data))?;
+                w.write_all(b"}")
+            }
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        match self {
+            Value::Bool(val) => Json::Bool(*val),
+            Value::F32(val) => float(*val),
+            Value::F64(val) => float(*val),
+            Value::Int(val) => Json::from(*val),
+            Value::UInt(val) => Json::from(*val),
+            Value::Str(val) => Json::String(val.to_string()),
+            Value::Bytes(val) => Json::String(base64(val)),
+            Value::List(list) => list.to_json(),
+            Value::Struct(entries) => entries.to_json(),
+            Value::Ext { tag, data } => {
+                let mut map = Map::new();
+                map.insert("tag".into(), Json::from(*tag));
+                map.insert("data".into(), Json::String(base64(data)));
+                Json::Object(map)
+            }
+        }
+    }
+}
+
+impl ToJson for List<'_> {
+    fn write_json(&self, w: &mut dyn Write) -> io::Result<()> {
+        match self {
+            List::Bool(items) => write_array(w, items, |w, val| write!(w, "{val}")),
+            List::F32(items) => {
+                write_array(w, items, |w, val| write_float(w, val.is_finite(), val))
+            }
+            List::F64(items) => {
+                write_array(w, items, |w, val| write_float(w, val.is_finite(), val))
+            }
+            List::Int(items) => write_array(w, items, |w, val| write!(w, "{val}")),
+            List::UInt(items) => write_array(w, items, |w, val| write!(w, "{val}")),
+            List::Str(items) => write_array(w, items, |w, val| write_str(w, val)),
+            List::Bytes(items) => write_array(w, items, |w, val| write_str(w, &base64(val))),
+            List::List(items) => write_array(w, items, |w, val| val.write_json(w)),
+            List::Struct(items) => write_array(w, items, |w, val| val.write_json(w)),
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        fn array<T>(items: &[T], f: impl Fn(&T) -> Json) -> Json {
+            Json::Array(items.iter().map(f).collect())
+        }
+        match self {
+            List::Bool(items) => array(items, |val| Json::Bool(*val)),
+            List::F32(items) => array(items, |val| float(*val)),
+            List::F64(items) => array(items, |val| float(*val)),
+            List::Int(items) => array(items, |val| Json::from(*val)),
+            List::UInt(items) => array(items, |val| Json::from(*val)),
+            List::Str(items) => array(items, |val| Json::String(val.to_string())),
+            List::Bytes(items) => array(items, |val| Json::String(base64(val))),
+            List::List(items) => array(items, ToJson::to_json),
+            List::Struct(items) => array(items, ToJson::to_json),
+        }
+    }
+}
+
+/// A repeated key is written once per value; in [`ToJson::to_json`] output the
+/// last value wins.
+impl ToJson for Entries<'_> {
+    fn write_json(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(b"{")?;
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b",")?;
+            }
+            write!(w, "\"{key}\":")?;
+            value.write_json(w)?;
+        }
+        w.write_all(b"}")
+    }
+
+    fn to_json(&self) -> Json {
+        let map = self
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_json()))
+            .collect();
+        Json::Object(map)
+    }
+}
+
+impl From<&Value<'_>> for Json {
+    fn from(value: &Value<'_>) -> Self {
+        value.to_json()
+    }
+}
+
+impl TryFrom<Json> for Value<'static> {
+    type Error = Error;
+
+    fn try_from(json: Json) -> Result<Self> {
+        from_value(json)
+    }
+}
+
+fn write_array<T>(
+    w: &mut dyn Write,
+    items: &[T],
+    mut item: impl FnMut(&mut dyn Write, &T) -> io::Result<()>,
+) -> io::Result<()> {
+    w.write_all(b"[")?;
+    for (i, val) in items.iter().enumerate() {
+        if i > 0 {
+            w.write_all(b",")?;
+        }
+        item(w, val)?;
+    }
+    w.write_all(b"]")
+}
+
+fn write_str(w: &mut dyn Write, val: &str) -> io::Result<()> {
+    serde_json::to_writer(w, val).map_err(io::Error::from)
+}
+
+/// `Debug` output is the shortest representation that reads back as the same
+/// float, and is always valid JSON for finite values.
+fn write_float(w: &mut dyn Write, finite: bool, val: &dyn std::fmt::Debug) -> io::Result<()> {
+    match finite {
+        true => write!(w, "{val:?}"),
+        false => w.write_all(b"null"),
+    }
+}
+
+/// Uses the shortest representation of an `f32` rather than that of the widened `f64`.
+fn float(val: impl std::fmt::Debug) -> Json {
+    let val: f64 = format!("{val:?}").parse().unwrap_or(f64::NAN);
+    Number::from_f64(val).map_or(Json::Null, Json::Number)
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    utils::write_base64(&mut out, bytes).unwrap();
+    out
+}
@@ -1,28 +1,62 @@
+mod cmp;
 mod convert;
+#[cfg(feature = "serde")]
+mod de;
 mod decoder;
+mod diff;
 mod encoder;
-mod entries;
+mod entries_ref;
+mod extension;
+mod options;
+mod owned;
+mod path;
+mod pointer;
 mod print;
+mod reader;
+#[cfg(feature = "serde")]
+mod ser;
+mod text;
 mod utils;
 mod varint;
 mod zig_zag;
 
+pub mod entries;
 pub mod errors;
+#[cfg(feature = "json")]
+pub mod json;
 
 pub use cor_macro::*;
 #[doc(hidden)]
+pub use decoder::FieldDecoder;
+#[doc(hidden)]
 pub use encoder::FieldEncoder;
 
+pub use cmp::{Total, TotalOrd};
 pub use convert::ConvertFrom;
+pub use decoder::{skip_value, validate, validate_with};
+pub use diff::{Change, Diff};
 pub use entries::Entries;
+pub use entries_ref::{EntriesRef, ListIter, ListRef, ValueRef};
+pub use extension::{Ext, Extension, register_extension};
+pub use options::{DecodeOptions, DuplicateKeys};
+pub use path::{Path, Segment};
+pub use print::{BytesFormat, Print, Printer};
+#[cfg(feature = "serde")]
+pub use {
+    de::{from_slice, from_slice_with},
+    ser::{to_vec, to_writer},
+};
 
-pub type Error = Box<dyn std::error::Error + Send + Sync + 'static>;
+pub use errors::{Error, ErrorKind};
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-use std::io::{self, Write};
+use decoder::{Parser, Source};
+use std::borrow::Cow;
+use std::io::{self, Read, Write};
 
 #[doc(hidden)]
 pub mod __private {
+    pub use crate::decoder::{Parser, Source};
     pub use crate::encoder::encode_struct_field;
 }
 
@@ -32,38 +66,79 @@ pub trait Encoder {
 
 pub trait Decoder<'de>: Sized {
     fn parse(reader: &mut &'de [u8]) -> Result<Self> {
-        Self::decode(&Entries::parse(reader)?)
+        Self::parse_with(reader, &DecodeOptions::default())
+    }
+
+    fn parse_with(reader: &mut &'de [u8], options: &DecodeOptions) -> Result<Self> {
+        let mut parser = Parser::new(reader, options);
+        Self::parse_fields(&mut parser).map_err(|err| parser.locate(err))
+    }
+
+    /// Parses a message that must span all of `bytes`.
+    fn from_slice_exact(bytes: &'de [u8]) -> Result<Self> {
+        Self::from_slice_exact_with(bytes, &DecodeOptions::default())
+    }
+
+    fn from_slice_exact_with(mut bytes: &'de [u8], options: &DecodeOptions) -> Result<Self> {
+        let this = Self::parse_with(&mut bytes, options)?;
+        decoder::ensure_consumed(bytes)?;
+        Ok(this)
+    }
+
+    /// Reads exactly one message from `reader`, leaving it positioned right after
+    /// the message. Borrowed fields (`&str`, `&[u8]`) can't be decoded this way.
+    fn read_from(reader: impl Read) -> Result<Self> {
+        Self::read_from_with(reader, &DecodeOptions::default())
+    }
+
+    fn read_from_with(reader: impl Read, options: &DecodeOptions) -> Result<Self> {
+        let mut parser = Parser::over(reader::Stream::new(reader, options), options);
+        Self::parse_fields(&mut parser).map_err(|err| parser.locate(err))
     }
 
     fn decode(entries: &Entries<'de>) -> Result<Self>;
+
+    /// Decodes the fields of a struct that `p` is positioned inside. Derived
+    /// decoders convert each field as it's read; by default the fields are
+    /// parsed into [`Entries`] and [`decode`](Self::decode)d.
+    #[doc(hidden)]
+    fn parse_fields<'p: 'de, R: Source<'p>>(p: &mut Parser<'_, 'p, R>) -> Result<Self> {
+        Self::decode(&p.entries()?)
+    }
 }
 
 pub trait IntoValue<'de> {
     fn to_value(&self) -> Value<'de>;
 }
 
-#[derive(Clone)]
+/// A dynamically typed value.
+///
+/// `==` compares floats by IEEE 754 rules, so a value holding `NaN` isn't equal
+/// to itself; wrap values in [`Total`] for an `Eq`, `Ord` and `Hash` that compare
+/// floats by bit pattern instead.
+#[derive(Clone, PartialEq)]
 pub enum Value<'de> {
     Bool(bool),
     F32(f32),
     F64(f64),
     Int(i64),
     UInt(u64),
-    Str(&'de str),
-    Bytes(&'de [u8]),
+    Str(Cow<'de, str>),
+    Bytes(Cow<'de, [u8]>),
     List(List<'de>),
     Struct(Entries<'de>),
+    Ext { tag: u64, data: Cow<'de, [u8]> },
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
 pub enum List<'de> {
     Bool(Vec<bool>),
     F32(Vec<f32>),
     F64(Vec<f64>),
     Int(Vec<i64>),
     UInt(Vec<u64>),
-    Str(Vec<&'de str>),
-    Bytes(Vec<&'de [u8]>),
+    Str(Vec<Cow<'de, str>>),
+    Bytes(Vec<Cow<'de, [u8]>>),
     List(Vec<List<'de>>),
     Struct(Vec<Entries<'de>>),
 }
@@ -0,0 +1,52 @@
+/// Settings applied while parsing untrusted input.
+///
+/// List capacity is always capped by the remaining input, so a length prefix alone
+/// can't trigger a large allocation; the limits below bound the rest.
+#[derive(Debug, Clone, Copy)]
+pub struct DecodeOptions {
+    /// Maximum nesting of structs and lists. The outermost struct is at depth 1.
+    pub max_depth: usize,
+    /// Maximum number of elements in a single list.
+    pub max_list_len: usize,
+    /// Maximum number of values in a message, counting struct fields and list elements.
+    pub max_elements: usize,
+    /// Maximum size of an encoded message in bytes.
+    pub max_bytes: usize,
+    /// What to do when a struct repeats a key.
+    pub duplicate_keys: DuplicateKeys,
+}
+
+/// How a struct that repeats a key is parsed.
+///
+/// Different decoders resolving repeated keys differently lets a message mean
+/// one thing to the service that checks it and another to the one that acts on
+/// it, so repeated keys are rejected unless another policy is chosen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DuplicateKeys {
+    /// Fail with [`ErrorKind::DuplicateKey`](crate::ErrorKind::DuplicateKey).
+    #[default]
+    Reject,
+    /// Keep the value of the first occurrence.
+    FirstWins,
+    /// Keep the value of the last occurrence.
+    LastWins,
+    /// Concatenate lists of the same element type, like protobuf repeated
+    /// fields; for any other value the last occurrence wins.
+    MergeLists,
+}
+
+impl DecodeOptions {
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_list_len: u32::MAX as usize,
+            max_elements: usize::MAX,
+            max_bytes: usize::MAX,
+            duplicate_keys: DuplicateKeys::default(),
+        }
+    }
+}
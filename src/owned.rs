@@ -0,0 +1,82 @@
+use crate::{List, Value};
+use std::borrow::Cow;
+
+fn borrow<T: ?Sized + ToOwned>(val: &T) -> Cow<'_, T> {
+    Cow::Borrowed(val)
+}
+
+fn into_owned<T: ?Sized + ToOwned>(val: Cow<'_, T>) -> Cow<'static, T> {
+    Cow::Owned(val.into_owned())
+}
+
+impl Value<'_> {
+    /// Copies any borrowed data so the value no longer depends on the input buffer.
+    pub fn into_owned(self) -> Value<'static> {
+        match self {
+            Value::Bool(val) => Value::Bool(val),
+            Value::F32(val) => Value::F32(val),
+            Value::F64(val) => Value::F64(val),
+            Value::Int(val) => Value::Int(val),
+            Value::UInt(val) => Value::UInt(val),
+            Value::Str(val) => Value::Str(into_owned(val)),
+            Value::Bytes(val) => Value::Bytes(into_owned(val)),
+            Value::List(list) => Value::List(list.into_owned()),
+            Value::Struct(entries) => Value::Struct(entries.into_owned()),
+            Value::Ext { tag, data } => Value::Ext {
+                tag,
+                data: into_owned(data),
+            },
+        }
+    }
+
+    /// Returns a view of this value that borrows strings and bytes from `self`.
+    pub fn as_borrowed(&self) -> Value<'_> {
+        match self {
+            Value::Bool(val) => Value::Bool(*val),
+            Value::F32(val) => Value::F32(*val),
+            Value::F64(val) => Value::F64(*val),
+            Value::Int(val) => Value::Int(*val),
+            Value::UInt(val) => Value::UInt(*val),
+            Value::Str(val) => Value::Str(borrow(&**val)),
+            Value::Bytes(val) => Value::Bytes(borrow(&**val)),
+            Value::List(list) => Value::List(list.as_borrowed()),
+            Value::Struct(entries) => Value::Struct(entries.as_borrowed()),
+            Value::Ext { tag, data } => Value::Ext {
+                tag: *tag,
+                data: borrow(&**data),
+            },
+        }
+    }
+}
+
+impl List<'_> {
+    pub fn into_owned(self) -> List<'static> {
+        match self {
+            List::Bool(items) => List::Bool(items),
+            List::F32(items) => List::F32(items),
+            List::F64(items) => List::F64(items),
+            List::Int(items) => List::Int(items),
+            List::UInt(items) => List::UInt(items),
+            List::Str(items) => List::Str(items.into_iter().map(into_owned).collect()),
+            List::Bytes(items) => List::Bytes(items.into_iter().map(into_owned).collect()),
+            List::List(items) => List::List(items.into_iter().map(List::into_owned).collect()),
+            List::Struct(items) => {
+                List::Struct(items.into_iter().map(|e| e.into_owned()).collect())
+            }
+        }
+    }
+
+    pub fn as_borrowed(&self) -> List<'_> {
+        match self {
+            List::Bool(items) => List::Bool(items.clone()),
+            List::F32(items) => List::F32(items.clone()),
+            List::F64(items) => List::F64(items.clone()),
+            List::Int(items) => List::Int(items.clone()),
+            List::UInt(items) => List::UInt(items.clone()),
+            List::Str(items) => List::Str(items.iter().map(|item| borrow(&**item)).collect()),
+            List::Bytes(items) => List::Bytes(items.iter().map(|item| borrow(&**item)).collect()),
+            List::List(items) => List::List(items.iter().map(List::as_borrowed).collect()),
+            List::Struct(items) => List::Struct(items.iter().map(|e| e.as_borrowed()).collect()),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{Entries, List, Path, Segment, Value};
+
+// A pointer such as `/21/0/3` steps into struct fields by key and list elements
+// by index; which one a token means depends on the container it's applied to.
+// A `Path` spells out which it is, so a key never indexes a list or vice versa.
+//
+// List elements are stored unboxed (`List::UInt(Vec<u64>)`, ...), so only struct
+// fields can be pointed at: a pointer must end on a key.
+
+enum Node<'a, 'de> {
+    Value(&'a Value<'de>),
+    List(&'a List<'de>),
+    Struct(&'a Entries<'de>),
+}
+
+enum NodeMut<'a, 'de> {
+    Value(&'a mut Value<'de>),
+    List(&'a mut List<'de>),
+    Struct(&'a mut Entries<'de>),
+}
+
+/// One step of a pointer or a path.
+trait Step {
+    fn key(&self) -> Option<u16>;
+    fn index(&self) -> Option<usize>;
+}
+
+impl Step for &str {
+    fn key(&self) -> Option<u16> {
+        self.parse().ok()
+    }
+
+    fn index(&self) -> Option<usize> {
+        self.parse().ok()
+    }
+}
+
+impl Step for &Segment {
+    fn key(&self) -> Option<u16> {
+        match self {
+            Segment::Key(key) => Some(*key),
+            Segment::Index(_) => None,
+        }
+    }
+
+    fn index(&self) -> Option<usize> {
+        match self {
+            Segment::Index(index) => Some(*index),
+            Segment::Key(_) => None,
+        }
+    }
+}
+
+fn tokens(pointer: &str) -> Option<std::str::Split<'_, char>> {
+    pointer.strip_prefix('/').map(|rest| rest.split('/'))
+}
+
+fn walk<'a, 'de>(
+    mut node: Node<'a, 'de>,
+    steps: impl IntoIterator<Item = impl Step>,
+) -> Option<&'a Value<'de>> {
+    for step in steps {
+        node = match node {
+            Node::Struct(entries) | Node::Value(Value::Struct(entries)) => {
+                Node::Value(entries.get(step.key()?)?)
+            }
+            Node::List(list) | Node::Value(Value::List(list)) => {
+                let index = step.index()?;
+                match list {
+                    List::List(lists) => Node::List(lists.get(index)?),
+                    List::Struct(structs) => Node::Struct(structs.get(index)?),
+                    _ => return None,
+                }
+            }
+            Node::Value(_) => return None,
+        };
+    }
+    match node {
+        Node::Value(value) => Some(value),
+        _ => None,
+    }
+}
+
+fn walk_mut<'a, 'de>(
+    mut node: NodeMut<'a, 'de>,
+    steps: impl IntoIterator<Item = impl Step>,
+) -> Option<&'a mut Value<'de>> {
+    for step in steps {
+        node = match node {
+            NodeMut::Struct(entries) | NodeMut::Value(Value::Struct(entries)) => {
+                NodeMut::Value(entries.get_mut(step.key()?)?)
+            }
+            NodeMut::List(list) | NodeMut::Value(Value::List(list)) => {
+                let index = step.index()?;
+                match list {
+                    List::List(lists) => NodeMut::List(lists.get_mut(index)?),
+                    List::Struct(structs) => NodeMut::Struct(structs.get_mut(index)?),
+                    _ => return None,
+                }
+            }
+            NodeMut::Value(_) => return None,
+        };
+    }
+    match node {
+        NodeMut::Value(value) => Some(value),
+        _ => None,
+    }
+}
+
+impl<'de> Value<'de> {
+    /// Looks up a nested value by a pointer such as `/21/0/3`, walking structs
+    /// by key and lists by index, like `serde_json::Value::pointer`.
+    ///
+    /// The empty pointer refers to `self`. Returns `None` if any step is missing.
+    ///
+    /// Only struct fields are stored as values, so `None` is also returned for a
+    /// pointer that is present but ends on a list element: an element of a scalar
+    /// list such as `List::UInt`, or an element of a `List::Struct` or
+    /// `List::List`. Point at a field of a struct element instead, or look up the
+    /// whole list and index it.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value<'de>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        walk(Node::Value(self), tokens(pointer)?)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value<'de>> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        walk_mut(NodeMut::Value(self), tokens(pointer)?)
+    }
+
+    /// Like [`Value::pointer`], but following a [`Path`] such as the one an
+    /// [`Error`](crate::Error) reports. A [`Segment::Key`] only steps into a struct
+    /// and a [`Segment::Index`] only into a list. The empty path refers to `self`.
+    pub fn get_path(&self, path: &Path) -> Option<&Value<'de>> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        walk(Node::Value(self), path.segments())
+    }
+}
+
+impl<'de> Entries<'de> {
+    /// Like [`Value::pointer`], starting from the fields of this struct.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value<'de>> {
+        walk(Node::Struct(self), tokens(pointer)?)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value<'de>> {
+        walk_mut(NodeMut::Struct(self), tokens(pointer)?)
+    }
+
+    /// Like [`Value::get_path`], starting from the fields of this struct.
+    pub fn get_path(&self, path: &Path) -> Option<&Value<'de>> {
+        walk(Node::Struct(self), path.segments())
+    }
+}
@@ -1,12 +1,333 @@
 use super::*;
 use std::fmt::{self, Debug, Write};
 
-impl std::fmt::Display for Value<'_> {
+/// How [`Printer`] writes `Bytes` and extension payloads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BytesFormat {
+    /// `(1 2 255)`, as the text format reads them.
+    Decimal,
+    /// `x"0102ff"`.
+    #[default]
+    Hex,
+    /// `b64"AQL/"`, standard alphabet with padding.
+    Base64,
+}
+
+/// Settings for rendering a [`Value`], [`List`] or [`Entries`] for people to read.
+///
+/// `Display` uses the default printer: single-line, bytes in hex. The `#` flag
+/// switches to [`Printer::pretty`] and a precision such as `{:.16}` sets
+/// [`Printer::max_bytes`].
+#[derive(Debug, Clone)]
+pub struct Printer {
+    /// Puts each struct field, and each element of a list of lists or structs,
+    /// on its own line.
+    pub pretty: bool,
+    /// Spaces per nesting level in pretty mode.
+    pub indent: usize,
+    pub bytes: BytesFormat,
+    /// Number of bytes shown of a longer blob; the rest is elided as `…+N`.
+    pub max_bytes: Option<usize>,
+    /// Writes every scalar with its type, e.g. `uint(42)` or `str("name")`.
+    pub annotate_types: bool,
+    /// Renders extension values with the `Debug` of the type registered through
+    /// [`register_extension`](crate::register_extension).
+    pub render_extensions: bool,
+}
+
+impl Printer {
+    pub const DEFAULT_INDENT: usize = 4;
+
+    /// Wraps `value` so that it's formatted with these settings.
+    pub fn display<'a, T: Print + ?Sized>(&'a self, value: &'a T) -> impl fmt::Display + 'a {
+        Rendered {
+            printer: self,
+            value,
+        }
+    }
+
+    fn from_flags(f: &fmt::Formatter<'_>) -> Self {
+        Self {
+            pretty: f.alternate(),
+            max_bytes: f.precision(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self {
+            pretty: false,
+            indent: Self::DEFAULT_INDENT,
+            bytes: BytesFormat::default(),
+            max_bytes: None,
+            annotate_types: false,
+            render_extensions: true,
+        }
+    }
+}
+
+/// Values a [`Printer`] can render.
+pub trait Print {
+    #[doc(hidden)]
+    fn print(&self, w: &mut Writer<'_, impl Write>) -> fmt::Result;
+}
+
+impl Print for Value<'_> {
+    fn print(&self, w: &mut Writer<'_, impl Write>) -> fmt::Result {
+        w.value(self)
+    }
+}
+
+impl Print for List<'_> {
+    fn print(&self, w: &mut Writer<'_, impl Write>) -> fmt::Result {
+        w.list(self)
+    }
+}
+
+impl Print for Entries<'_> {
+    fn print(&self, w: &mut Writer<'_, impl Write>) -> fmt::Result {
+        w.entries(self)
+    }
+}
+
+struct Rendered<'a, T: ?Sized> {
+    printer: &'a Printer,
+    value: &'a T,
+}
+
+impl<T: Print + ?Sized> fmt::Display for Rendered<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        Debug::fmt(self, f)
+        self.printer.write(f, self.value)
+    }
+}
+
+macro_rules! impl_display {
+    [$($ty:ident)*] => {$(
+        impl fmt::Display for $ty<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Printer::from_flags(f).write(f, self)
+            }
+        }
+    )*};
+}
+
+impl_display! { Value List Entries }
+
+impl Printer {
+    pub(crate) fn write<T: Print + ?Sized>(&self, out: impl Write, value: &T) -> fmt::Result {
+        value.print(&mut Writer {
+            printer: self,
+            out,
+            depth: 0,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct Writer<'p, W> {
+    printer: &'p Printer,
+    out: W,
+    depth: usize,
+}
+
+impl<W: Write> Writer<'_, W> {
+    fn value(&mut self, value: &Value) -> fmt::Result {
+        match value {
+            Value::Bool(val) => self.scalar("bool", |w| write!(w.out, "{val}")),
+            Value::F32(val) => self.float("f32", val, "f"),
+            Value::F64(val) => self.float("f64", val, ""),
+            Value::Int(val) => self.scalar("int", |w| write!(w.out, "{val}")),
+            Value::UInt(val) => match self.printer.annotate_types {
+                true => write!(self.out, "uint({val})"),
+                false => write!(self.out, "{val}u"),
+            },
+            Value::Str(val) => self.scalar("str", |w| w.str(val)),
+            Value::Bytes(val) => self.scalar("bytes", |w| w.bytes(val)),
+            Value::List(list) => self.list(list),
+            Value::Struct(entries) => self.entries(entries),
+            Value::Ext { tag, data } => {
+                let rendered = (self.printer.render_extensions)
+                    .then(|| render_extension(*tag, data))
+                    .flatten();
+                if let Some(rendered) = rendered {
+                    return self.out.write_str(&rendered);
+                }
+                write!(self.out, "#{tag}")?;
+                match self.printer.bytes {
+                    BytesFormat::Decimal => self.bytes(data),
+                    // Keep the payload in parentheses so it reads as part of the tag.
+                    _ => {
+                        self.out.write_char('(')?;
+                        self.bytes(data)?;
+                        self.out.write_char(')')
+                    }
+                }
+            }
+        }
+    }
+
+    fn scalar(&mut self, ty: &str, f: impl FnOnce(&mut Self) -> fmt::Result) -> fmt::Result {
+        if !self.printer.annotate_types {
+            return f(self);
+        }
+        write!(self.out, "{ty}(")?;
+        f(self)?;
+        self.out.write_char(')')
+    }
+
+    fn float(&mut self, ty: &str, val: &dyn Debug, suffix: &str) -> fmt::Result {
+        match self.printer.annotate_types {
+            true => write!(self.out, "{ty}({val:?})"),
+            false => write!(self.out, "{val:?}{suffix}"),
+        }
+    }
+
+    fn str(&mut self, val: &str) -> fmt::Result {
+        let out = &mut self.out;
+        out.write_char('"')?;
+        for ch in val.chars() {
+            match ch {
+                '"' => out.write_str("\\\"")?,
+                '\\' => out.write_str("\\\\")?,
+                '\n' => out.write_str("\\n")?,
+                '\r' => out.write_str("\\r")?,
+                '\t' => out.write_str("\\t")?,
+                '\0' => out.write_str("\\0")?,
+                ch if ch.is_control() => write!(out, "\\u{{{:x}}}", ch as u32)?,
+                ch => out.write_char(ch)?,
+            }
+        }
+        out.write_char('"')
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> fmt::Result {
+        let shown = bytes
+            .len()
+            .min(self.printer.max_bytes.unwrap_or(usize::MAX));
+        let (shown, elided) = (&bytes[..shown], bytes.len() - shown);
+        let out = &mut self.out;
+        let (open, close) = match self.printer.bytes {
+            BytesFormat::Decimal => ("(", ")"),
+            BytesFormat::Hex => ("x\"", "\""),
+            BytesFormat::Base64 => ("b64\"", "\""),
+        };
+        out.write_str(open)?;
+        match self.printer.bytes {
+            BytesFormat::Decimal => {
+                for (i, byte) in shown.iter().enumerate() {
+                    if i > 0 {
+                        out.write_char(' ')?;
+                    }
+                    write!(out, "{byte}")?;
+                }
+            }
+            BytesFormat::Hex => {
+                for byte in shown {
+                    write!(out, "{byte:02x}")?;
+                }
+            }
+            BytesFormat::Base64 => utils::write_base64(out, shown)?,
+        }
+        if elided > 0 {
+            let space = match (self.printer.bytes, shown.is_empty()) {
+                (BytesFormat::Decimal, false) => " ",
+                _ => "",
+            };
+            write!(out, "{space}…+{elided}")?;
+        }
+        out.write_str(close)
+    }
+
+    fn list(&mut self, list: &List) -> fmt::Result {
+        match list {
+            List::Bool(items) => self.items(items, "bool", |w, val| w.value(&Value::Bool(*val))),
+            List::F32(items) => self.items(items, "f32", |w, val| w.float("f32", val, "f")),
+            List::F64(items) => self.items(items, "f64", |w, val| w.float("f64", val, "")),
+            List::Int(items) => self.items(items, "int", |w, val| w.value(&Value::Int(*val))),
+            List::UInt(items) => self.items(items, "uint", |w, val| w.value(&Value::UInt(*val))),
+            List::Str(items) => self.items(items, "str", |w, val| w.scalar("str", |w| w.str(val))),
+            List::Bytes(items) => {
+                self.items(items, "bytes", |w, val| w.scalar("bytes", |w| w.bytes(val)))
+            }
+            List::List(items) => self.items(items, "list", Self::list),
+            List::Struct(items) => self.items(items, "struct", Self::entries),
+        }
+    }
+
+    fn items<T>(
+        &mut self,
+        items: &[T],
+        ty: &str,
+        mut item: impl FnMut(&mut Self, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        if items.is_empty() {
+            return write!(self.out, "[]{ty}");
+        }
+        // Scalars stay on one line even when pretty printing.
+        let multiline = matches!(ty, "list" | "struct");
+        self.group('[', ']', items, multiline, |w, val| item(w, val))
+    }
+
+    fn entries(&mut self, entries: &Entries) -> fmt::Result {
+        if entries.is_empty() {
+            return self.out.write_str("{}");
+        }
+        self.group('{', '}', entries.fields(), true, |w, (key, value)| {
+            write!(w.out, "{key}: ")?;
+            w.value(value)
+        })
+    }
+
+    fn group<T>(
+        &mut self,
+        open: char,
+        close: char,
+        items: &[T],
+        multiline: bool,
+        mut item: impl FnMut(&mut Self, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        let multiline = multiline && self.printer.pretty;
+        self.out.write_char(open)?;
+        self.depth += 1;
+        for (i, val) in items.iter().enumerate() {
+            match (multiline, i) {
+                (true, _) => self.newline()?,
+                (false, 0) => {}
+                (false, _) => self.out.write_str(", ")?,
+            }
+            item(self, val)?;
+            if multiline {
+                self.out.write_char(',')?;
+            }
+        }
+        self.depth -= 1;
+        if multiline {
+            self.newline()?;
+        }
+        self.out.write_char(close)
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        let width = self.depth * self.printer.indent;
+        write!(self.out, "\n{:width$}", "")
     }
 }
 
+fn render_extension(tag: u64, data: &[u8]) -> Option<String> {
+    struct Render<'a>(extension::Render, &'a [u8]);
+    impl fmt::Display for Render<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            (self.0)(self.1, f).unwrap_or(Err(fmt::Error))
+        }
+    }
+    let render = extension::renderer(tag)?;
+    let mut out = String::new();
+    write!(out, "{}", Render(render, data)).ok()?;
+    Some(out)
+}
+
 impl Debug for Value<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -30,6 +351,13 @@ impl Debug for Value<'_> {
             Value::Bool(val) => val.fmt(f),
             Value::List(list) => list.fmt(f),
             Value::Struct(items) => items.fmt(f),
+            Value::Ext { tag, data } => {
+                if let Some(result) = extension::renderer(*tag).and_then(|render| render(data, f)) {
+                    return result;
+                }
+                write!(f, "#{tag}")?;
+                Value::Bytes(Cow::Borrowed(data)).fmt(f)
+            }
         }
     }
 }
@@ -52,8 +380,6 @@ impl Debug for List<'_> {
 
 impl<'de> fmt::Debug for Entries<'de> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_map()
-            .entries(self.iter().map(|(k, v)| (k, v)))
-            .finish()
+        f.debug_map().entries(self.iter()).finish()
     }
 }
@@ -0,0 +1,68 @@
+use crate::decoder::{Source, limit_exceeded};
+use crate::{DecodeOptions, Error, ErrorKind, Result};
+use std::borrow::Cow;
+use std::io::Read;
+
+/// Reads a message straight from an [`io::Read`](std::io::Read) source, enforcing
+/// `max_bytes` before each read so a hostile stream is rejected before it's
+/// buffered rather than after.
+pub(crate) struct Stream<R> {
+    reader: R,
+    position: usize,
+    max_bytes: usize,
+}
+
+impl<R: Read> Stream<R> {
+    pub(crate) fn new(reader: R, options: &DecodeOptions) -> Self {
+        Self {
+            reader,
+            position: 0,
+            max_bytes: options.max_bytes,
+        }
+    }
+
+    fn advance(&mut self, len: usize) -> Result<()> {
+        match self.position.checked_add(len) {
+            Some(position) if position <= self.max_bytes => {
+                self.position = position;
+                Ok(())
+            }
+            _ => Err(limit_exceeded("max_bytes", self.max_bytes)),
+        }
+    }
+}
+
+impl<'de, R: Read> Source<'de> for Stream<R> {
+    fn read_byte(&mut self) -> Result<u8> {
+        self.read_array().map(|[byte]| byte)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        self.advance(N)?;
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf).map_err(Error::io)?;
+        Ok(buf)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Cow<'de, [u8]>> {
+        self.advance(len)?;
+        // Read through `take` so a bogus length can't reserve memory up front.
+        let mut buf = Vec::new();
+        (&mut self.reader)
+            .take(len as u64)
+            .read_to_end(&mut buf)
+            .map_err(Error::io)?;
+        if buf.len() < len {
+            return Err(ErrorKind::Eof.into());
+        }
+        Ok(Cow::Owned(buf))
+    }
+
+    fn position(&self) -> usize {
+        self.position
+    }
+
+    fn remaining(&self) -> usize {
+        self.max_bytes - self.position
+    }
+}
@@ -0,0 +1,503 @@
+// serde data model on the cor wire format:
+//
+// - Structs, and the fields of struct variants, use the key given by a numeric
+//   `#[serde(rename = "3")]`, or else their index among the fields serde sees
+//   (`#[serde(skip)]` fields don't count).
+// - Tuples and tuple structs become structs keyed `0..len`; newtype structs are
+//   transparent; unit and unit structs are empty structs.
+// - Sequences become lists, whose elements must all have the same wire type;
+//   a sequence of `u8`, like `Vec<u8>`, becomes bytes.
+// - Maps become lists of `{0: key, 1: value}` structs.
+// - A unit variant is its key as `UInt`; any other variant is a struct with one
+//   field, under the variant's key, holding the payload.
+// - `None` fields are left out; `None` anywhere else is an error.
+
+use crate::{Entries, Error, ErrorKind, List, Result, Value};
+use serde::ser::{self, Serialize};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::io::Write;
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::conversion(msg.to_string())
+    }
+}
+
+/// Serializes `value`, which must serialize as a struct, into a message.
+pub fn to_writer<T: Serialize + ?Sized>(mut writer: impl Write, value: &T) -> Result<()> {
+    let entries = to_entries(value)?;
+    crate::Encoder::encode(&entries, &mut writer).map_err(Error::io)
+}
+
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    to_writer(&mut buf, value)?;
+    Ok(buf)
+}
+
+pub(crate) fn to_entries<T: Serialize + ?Sized>(value: &T) -> Result<Entries<'static>> {
+    match value.serialize(Serializer::default())? {
+        Some(Value::Struct(entries)) => Ok(entries),
+        _ => Err(Error::conversion("a message must serialize as a struct")),
+    }
+}
+
+fn some(value: Option<Value<'static>>) -> Result<Value<'static>> {
+    value.ok_or_else(|| Error::conversion("`None` can only be a struct field"))
+}
+
+/// The key of the `index`th field or variant, named `name`.
+pub(crate) fn key(name: &str, index: usize) -> Result<u16> {
+    match name.parse() {
+        Ok(key) => Ok(key),
+        Err(_) => u16::try_from(index).map_err(Error::from),
+    }
+}
+
+/// Turns a serde value into a [`Value`], or `None` for an absent `Option`.
+///
+/// `is_u8` is set when a sequence element turns out to be a `u8`.
+#[derive(Default)]
+struct Serializer<'a> {
+    is_u8: Option<&'a Cell<bool>>,
+}
+
+type Output = Option<Value<'static>>;
+
+macro_rules! serialize_as {
+    [$($method:ident($ty:ty) => $name:ident($as:ty),)*] => {$(
+        fn $method(self, val: $ty) -> Result<Output> {
+            Ok(Some(Value::$name(<$as>::from(val))))
+        }
+    )*};
+}
+
+impl ser::Serializer for Serializer<'_> {
+    type Ok = Output;
+    type Error = Error;
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeStruct;
+    type SerializeTupleStruct = SerializeStruct;
+    type SerializeTupleVariant = SerializeVariant;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeVariant;
+
+    serialize_as! {
+        serialize_bool(bool) => Bool(bool),
+        serialize_i8(i8) => Int(i64),
+        serialize_i16(i16) => Int(i64),
+        serialize_i32(i32) => Int(i64),
+        serialize_i64(i64) => Int(i64),
+        serialize_u16(u16) => UInt(u64),
+        serialize_u32(u32) => UInt(u64),
+        serialize_u64(u64) => UInt(u64),
+        serialize_f32(f32) => F32(f32),
+        serialize_f64(f64) => F64(f64),
+    }
+
+    fn serialize_u8(self, val: u8) -> Result<Output> {
+        if let Some(is_u8) = self.is_u8 {
+            is_u8.set(true);
+        }
+        Ok(Some(Value::UInt(val.into())))
+    }
+
+    fn serialize_i128(self, val: i128) -> Result<Output> {
+        Ok(Some(Value::Int(i64::try_from(val)?)))
+    }
+
+    fn serialize_u128(self, val: u128) -> Result<Output> {
+        Ok(Some(Value::UInt(u64::try_from(val)?)))
+    }
+
+    fn serialize_char(self, val: char) -> Result<Output> {
+        Ok(Some(Value::Str(Cow::Owned(val.to_string()))))
+    }
+
+    fn serialize_str(self, val: &str) -> Result<Output> {
+        Ok(Some(Value::Str(Cow::Owned(val.to_string()))))
+    }
+
+    fn serialize_bytes(self, val: &[u8]) -> Result<Output> {
+        Ok(Some(Value::Bytes(Cow::Owned(val.to_vec()))))
+    }
+
+    fn serialize_none(self) -> Result<Output> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Output> {
+        some(value.serialize(self)?).map(Some)
+    }
+
+    fn serialize_unit(self) -> Result<Output> {
+        Ok(Some(Value::Struct(Entries::new())))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Output> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+    ) -> Result<Output> {
+        let key = key(variant, index as usize)?;
+        Ok(Some(Value::UInt(key.into())))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Output> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Output> {
+        let mut entries = Entries::new();
+        let value = some(value.serialize(Serializer::default())?)?;
+        entries.insert(key(variant, index as usize)?, value);
+        Ok(Some(Value::Struct(entries)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq> {
+        Ok(SerializeSeq {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            bytes: true,
+        })
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<SerializeStruct> {
+        Ok(SerializeStruct::default())
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<SerializeStruct> {
+        Ok(SerializeStruct::default())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SerializeVariant {
+            key: key(variant, index as usize)?,
+            inner: SerializeStruct::default(),
+        })
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<SerializeMap> {
+        Ok(SerializeMap::default())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<SerializeStruct> {
+        Ok(SerializeStruct::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(SerializeVariant {
+            key: key(variant, index as usize)?,
+            inner: SerializeStruct::default(),
+        })
+    }
+}
+
+/// Elements of a sequence; `bytes` holds while every element is a `u8`.
+struct SerializeSeq {
+    items: Vec<Value<'static>>,
+    bytes: bool,
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let is_u8 = Cell::new(false);
+        let value = value.serialize(Serializer {
+            is_u8: Some(&is_u8),
+        })?;
+        self.bytes &= is_u8.get();
+        self.items.push(some(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        if self.bytes && !self.items.is_empty() {
+            let bytes = self.items.into_iter().map(|item| match item {
+                Value::UInt(val) => val as u8,
+                _ => unreachable!("only `u8` elements set `is_u8`"),
+            });
+            return Ok(Some(Value::Bytes(Cow::Owned(bytes.collect()))));
+        }
+        list(self.items).map(|list| Some(Value::List(list)))
+    }
+}
+
+/// Packs values of the same wire type into a list.
+pub(crate) fn list(items: Vec<Value<'_>>) -> Result<List<'_>> {
+    let mut items = items.into_iter();
+    let Some(first) = items.next() else {
+        return Ok(List::Struct(Vec::new()));
+    };
+    macro_rules! collect {
+        [$($name:ident)*] => {
+            match first {
+                $(Value::$name(first) => {
+                    let mut list = vec![first];
+                    for item in items {
+                        match item {
+                            Value::$name(item) => list.push(item),
+                            _ => return Err(Error::conversion(
+                                "list elements must all have the same type",
+                            )),
+                        }
+                    }
+                    Ok(List::$name(list))
+                })*
+                Value::Ext { .. } => Err(Error::conversion(
+                    "lists of extension values aren't supported",
+                )),
+            }
+        };
+    }
+    collect![Bool F32 F64 Int UInt Str Bytes List Struct]
+}
+
+/// Fields keyed by an explicit numeric name or their position.
+#[derive(Default)]
+struct SerializeStruct {
+    entries: Entries<'static>,
+    index: usize,
+    /// Keys of the named fields so far, including `None` ones, so a rename
+    /// that lands on another field's key is caught.
+    keys: HashSet<u16>,
+}
+
+impl SerializeStruct {
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> Result<()> {
+        let key = key(name, self.index)?;
+        if !self.keys.insert(key) {
+            return Err(ErrorKind::DuplicateKey { key }.into());
+        }
+        self.index += 1;
+        if let Some(value) = value.serialize(Serializer::default())? {
+            self.entries.insert(key, value);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        Ok(Some(Value::Struct(self.entries)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.field(name, value)
+    }
+
+    fn skip_field(&mut self, _: &'static str) -> Result<()> {
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        SerializeStruct::end(self)
+    }
+}
+
+impl ser::SerializeTuple for SerializeStruct {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let value = some(value.serialize(Serializer::default())?)?;
+        self.entries.insert(key("", self.index)?, value);
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        SerializeStruct::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeStruct {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeTuple::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Output> {
+        SerializeStruct::end(self)
+    }
+}
+
+/// Wraps the payload of a variant in a struct with a single field.
+struct SerializeVariant {
+    key: u16,
+    inner: SerializeStruct,
+}
+
+impl SerializeVariant {
+    fn end(self) -> Result<Output> {
+        let mut entries = Entries::new();
+        entries.insert(self.key, some(self.inner.end()?)?);
+        Ok(Some(Value::Struct(entries)))
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        ser::SerializeTuple::serialize_element(&mut self.inner, value)
+    }
+
+    fn end(self) -> Result<Output> {
+        SerializeVariant::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.inner.field(name, value)
+    }
+
+    fn skip_field(&mut self, _: &'static str) -> Result<()> {
+        self.inner.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        SerializeVariant::end(self)
+    }
+}
+
+#[derive(Default)]
+struct SerializeMap {
+    entries: Vec<Entries<'static>>,
+    key: Option<Value<'static>>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Output;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(some(key.serialize(Serializer::default())?)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let mut entry = Entries::new();
+        if let Some(key) = self.key.take() {
+            entry.insert(0, key);
+        }
+        if let Some(value) = value.serialize(Serializer::default())? {
+            entry.insert(1, value);
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Output> {
+        Ok(Some(Value::List(List::Struct(self.entries))))
+    }
+}
+
+/// Writes the dynamic value as-is: structs become maps keyed by `u16`, and
+/// extension values become `Ext { tag, data }` structs. This isn't the mapping
+/// used by [`to_writer`], which expects a struct with named fields.
+impl Serialize for Value<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Bool(val) => serializer.serialize_bool(*val),
+            Value::F32(val) => serializer.serialize_f32(*val),
+            Value::F64(val) => serializer.serialize_f64(*val),
+            Value::Int(val) => serializer.serialize_i64(*val),
+            Value::UInt(val) => serializer.serialize_u64(*val),
+            Value::Str(val) => serializer.serialize_str(val),
+            Value::Bytes(val) => serializer.serialize_bytes(val),
+            Value::List(list) => list.serialize(serializer),
+            Value::Struct(entries) => entries.serialize(serializer),
+            Value::Ext { tag, data } => {
+                use ser::SerializeStruct;
+                let mut ext = serializer.serialize_struct("Ext", 2)?;
+                ext.serialize_field("tag", tag)?;
+                ext.serialize_field("data", &Bytes(data))?;
+                ext.end()
+            }
+        }
+    }
+}
+
+impl Serialize for List<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            List::Bool(items) => serializer.collect_seq(items),
+            List::F32(items) => serializer.collect_seq(items),
+            List::F64(items) => serializer.collect_seq(items),
+            List::Int(items) => serializer.collect_seq(items),
+            List::UInt(items) => serializer.collect_seq(items),
+            List::Str(items) => serializer.collect_seq(items),
+            List::Bytes(items) => serializer.collect_seq(items.iter().map(|val| Bytes(val))),
+            List::List(items) => serializer.collect_seq(items),
+            List::Struct(items) => serializer.collect_seq(items),
+        }
+    }
+}
+
+/// A repeated key is written once per value.
+impl Serialize for Entries<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self)
+    }
+}
+
+/// Writes a blob with `serialize_bytes` rather than as a sequence.
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
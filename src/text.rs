@@ -0,0 +1,433 @@
+use crate::utils;
+use crate::{BytesFormat, Printer};
+use crate::{DecodeOptions, Entries, Error, ErrorKind, List, Path, Result, Segment, Value};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+// The text format spells out every value so that it parses back to the same type:
+//
+//   true  -7  7u  1.5  1.5f  "esc\"aped"  (1 2 3)  #7(1 2)  [1u, 2u]  []str  {1: "a"}
+//
+// Bytes are also read in the hex and base64 forms that `Display` prints, e.g.
+// `x"0102ff"`, `b64"AQL/"` and `#7(x"0102")`.
+//
+// Floats are printed with their shortest round-trip representation (`NaN`, `inf`
+// and `-inf` included), strings escape quotes, backslashes and control characters,
+// and an empty list carries its element type after the brackets. Whitespace is
+// insignificant, trailing commas are allowed and `//` starts a line comment.
+
+const TEXT: Printer = Printer {
+    pretty: false,
+    indent: Printer::DEFAULT_INDENT,
+    bytes: BytesFormat::Decimal,
+    max_bytes: None,
+    annotate_types: false,
+    render_extensions: false,
+};
+
+impl Value<'_> {
+    /// Renders the value in the text format, which [`Value::from_text`] reads back.
+    pub fn to_text(&self) -> String {
+        TEXT.display(self).to_string()
+    }
+}
+
+impl<'de> Value<'de> {
+    /// Parses a value written in the text format. Strings without escapes
+    /// borrow from `text`.
+    ///
+    /// Parsing [`Value::to_text`] output yields an equal value, except that the
+    /// sign and payload of a `NaN` aren't preserved.
+    pub fn from_text(text: &'de str) -> Result<Self> {
+        Parser::new(text).parse(Parser::value)
+    }
+}
+
+impl Entries<'_> {
+    /// Renders the struct in the text format; see [`Value::to_text`].
+    pub fn to_text(&self) -> String {
+        TEXT.display(self).to_string()
+    }
+}
+
+impl<'de> Entries<'de> {
+    /// Parses a struct written in the text format, e.g. `{1: "name", 2: 42u}`.
+    /// Like [`Entries::parse`], a repeated key is an error.
+    pub fn from_text(text: &'de str) -> Result<Self> {
+        Parser::new(text).parse(Parser::entries)
+    }
+}
+
+struct Parser<'de> {
+    text: &'de str,
+    pos: usize,
+    path: Vec<Segment>,
+    depth_left: usize,
+}
+
+impl<'de> Parser<'de> {
+    fn new(text: &'de str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            path: Vec::new(),
+            depth_left: DecodeOptions::DEFAULT_MAX_DEPTH,
+        }
+    }
+
+    /// Runs `f` on the whole input, which must hold nothing else.
+    fn parse<T>(mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let parsed = f(&mut self)?;
+        self.skip_whitespace();
+        if self.pos < self.text.len() {
+            return Err(self.error("unexpected characters after the value"));
+        }
+        Ok(parsed)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> Error {
+        let kind = ErrorKind::Syntax {
+            message: message.into(),
+        };
+        self.fail_at(pos, kind)
+    }
+
+    fn fail_at(&self, pos: usize, kind: ErrorKind) -> Error {
+        Error::new(kind).located(Some(pos), Path::from(self.path.clone()))
+    }
+
+    fn rest(&self) -> &'de str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if !trimmed.starts_with("//") {
+                return;
+            }
+            self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+
+    /// Skips whitespace and consumes `ch` if it comes next.
+    fn eat(&mut self, ch: char) -> bool {
+        self.skip_whitespace();
+        let found = self.peek() == Some(ch);
+        if found {
+            self.pos += ch.len_utf8();
+        }
+        found
+    }
+
+    fn expect(&mut self, ch: char) -> Result<()> {
+        match self.eat(ch) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected `{ch}`"))),
+        }
+    }
+
+    /// Consumes a run of characters that can make up a number or a keyword.
+    fn word(&mut self) -> &'de str {
+        let rest = self.rest();
+        let mut prev = None;
+        let len = rest
+            .find(|ch: char| {
+                let exponent_sign = matches!(ch, '+' | '-') && matches!(prev, Some('e' | 'E'));
+                prev = Some(ch);
+                !(ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_') || exponent_sign)
+            })
+            .unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn value(&mut self) -> Result<Value<'de>> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.nested(|p| p.entries().map(Value::Struct)),
+            Some('[') => self.nested(|p| p.list().map(Value::List)),
+            Some('(') => self.bytes().map(Value::Bytes),
+            Some(_) if self.at_encoded_bytes() => self.bytes().map(Value::Bytes),
+            Some('"') => self.string().map(Value::Str),
+            Some('#') => {
+                self.pos += 1;
+                let tag = self.word();
+                let tag = tag
+                    .parse()
+                    .map_err(|_| self.error(format!("invalid extension tag `{tag}`")))?;
+                Ok(Value::Ext {
+                    tag,
+                    data: self.ext_data()?,
+                })
+            }
+            Some(_) => self.scalar(),
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn nested<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.depth_left == 0 {
+            let max_depth = DecodeOptions::DEFAULT_MAX_DEPTH;
+            return Err(self.fail_at(self.pos, ErrorKind::DepthLimitExceeded { max_depth }));
+        }
+        self.depth_left -= 1;
+        let result = f(self);
+        self.depth_left += 1;
+        result
+    }
+
+    fn scalar(&mut self) -> Result<Value<'de>> {
+        let start = self.pos;
+        let negative = self.rest().starts_with('-');
+        if negative {
+            self.pos += 1;
+        }
+        let word = self.word();
+        let token = &self.text[start..self.pos];
+        if word.is_empty() {
+            return Err(self.error("expected a value"));
+        }
+        let invalid = || self.error_at(start, format!("invalid value `{token}`"));
+        Ok(match word {
+            "true" if !negative => Value::Bool(true),
+            "false" if !negative => Value::Bool(false),
+            "NaN" | "inf" => Value::F64(token.parse().map_err(|_| invalid())?),
+            _ if word.ends_with('u') && !negative => {
+                Value::UInt(word[..word.len() - 1].parse().map_err(|_| invalid())?)
+            }
+            _ if word.ends_with('f') => {
+                Value::F32(token[..token.len() - 1].parse().map_err(|_| invalid())?)
+            }
+            _ if word.contains(['.', 'e', 'E']) => {
+                Value::F64(token.parse().map_err(|_| invalid())?)
+            }
+            _ => Value::Int(token.parse().map_err(|_| invalid())?),
+        })
+    }
+
+    fn string(&mut self) -> Result<Cow<'de, str>> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut owned: Option<String> = None;
+        loop {
+            let rest = self.rest();
+            let Some(end) = rest.find(['"', '\\']) else {
+                return Err(self.error("unterminated string"));
+            };
+            if let Some(owned) = &mut owned {
+                owned.push_str(&rest[..end]);
+            }
+            self.pos += end;
+            if rest[end..].starts_with('"') {
+                self.pos += 1;
+                return Ok(match owned {
+                    Some(owned) => Cow::Owned(owned),
+                    None => Cow::Borrowed(&self.text[start..self.pos - 1]),
+                });
+            }
+            let owned = owned.get_or_insert_with(|| self.text[start..self.pos].to_string());
+            owned.push(self.escape()?);
+        }
+    }
+
+    /// Consumes the escape sequence the cursor points at.
+    fn escape(&mut self) -> Result<char> {
+        let escape_start = self.pos;
+        let rest = &self.rest()[1..];
+        let (ch, len) = match rest.chars().next() {
+            Some('"') => ('"', 1),
+            Some('\\') => ('\\', 1),
+            Some('n') => ('\n', 1),
+            Some('r') => ('\r', 1),
+            Some('t') => ('\t', 1),
+            Some('0') => ('\0', 1),
+            Some('u') => {
+                let ch = (rest.strip_prefix("u{"))
+                    .and_then(|hex| hex.split_once('}'))
+                    .and_then(|(hex, _)| {
+                        let ch = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)?;
+                        Some((ch, hex.len() + 3))
+                    });
+                ch.ok_or_else(|| self.error("invalid unicode escape"))?
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        };
+        self.pos = escape_start + 1 + len;
+        Ok(ch)
+    }
+
+    fn at_encoded_bytes(&self) -> bool {
+        self.rest().starts_with("x\"") || self.rest().starts_with("b64\"")
+    }
+
+    fn bytes(&mut self) -> Result<Cow<'de, [u8]>> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.rest().starts_with("x\"") {
+            self.pos += 1;
+            let text = self.quoted()?;
+            return hex_decode(text)
+                .map(Cow::Owned)
+                .ok_or_else(|| self.error_at(start, "invalid hex bytes"));
+        }
+        if self.rest().starts_with("b64\"") {
+            self.pos += 3;
+            let text = self.quoted()?;
+            return utils::decode_base64(text)
+                .map(Cow::Owned)
+                .ok_or_else(|| self.error_at(start, "invalid base64 bytes"));
+        }
+        self.expect('(')?;
+        let mut bytes = Vec::new();
+        while !self.eat(')') {
+            let byte = self.word();
+            match byte.parse() {
+                Ok(byte) => bytes.push(byte),
+                Err(_) if byte.is_empty() => return Err(self.error("expected a byte or `)`")),
+                Err(_) => return Err(self.error(format!("invalid byte `{byte}`"))),
+            }
+        }
+        Ok(Cow::Owned(bytes))
+    }
+
+    /// Consumes a `"`-delimited run without escapes, returning what's between the quotes.
+    fn quoted(&mut self) -> Result<&'de str> {
+        self.pos += 1;
+        let rest = self.rest();
+        let Some(end) = rest.find('"') else {
+            return Err(self.error("unterminated bytes"));
+        };
+        self.pos += end + 1;
+        Ok(&rest[..end])
+    }
+
+    /// Reads an extension payload, which hex and base64 wrap in parentheses.
+    fn ext_data(&mut self) -> Result<Cow<'de, [u8]>> {
+        let start = self.pos;
+        if self.eat('(') {
+            self.skip_whitespace();
+            if self.at_encoded_bytes() {
+                let data = self.bytes()?;
+                self.expect(')')?;
+                return Ok(data);
+            }
+        }
+        self.pos = start;
+        self.bytes()
+    }
+
+    fn entries(&mut self) -> Result<Entries<'de>> {
+        self.expect('{')?;
+        let mut entries = Entries::new();
+        let mut keys = HashSet::new();
+        while !self.eat('}') {
+            let start = self.pos;
+            let key = self.word();
+            let key = key
+                .parse()
+                .map_err(|_| self.error_at(start, format!("expected a key, found `{key}`")))?;
+            if !keys.insert(key) {
+                return Err(self.fail_at(start, ErrorKind::DuplicateKey { key }));
+            }
+            self.expect(':')?;
+            self.path.push(Segment::Key(key));
+            let value = self.value()?;
+            self.path.pop();
+            entries.push(key, value);
+            if !self.eat(',') {
+                self.expect('}')?;
+                break;
+            }
+        }
+        Ok(entries)
+    }
+
+    fn list(&mut self) -> Result<List<'de>> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        while !self.eat(']') {
+            self.path.push(Segment::Index(items.len()));
+            self.skip_whitespace();
+            let start = self.pos;
+            let item = self.value()?;
+            self.path.pop();
+            items.push((start, item));
+            if !self.eat(',') {
+                self.expect(']')?;
+                break;
+            }
+        }
+        if items.is_empty() {
+            return self.empty_list();
+        }
+        let mut list = match &items[0].1 {
+            Value::Bool(_) => List::Bool(Vec::new()),
+            Value::F32(_) => List::F32(Vec::new()),
+            Value::F64(_) => List::F64(Vec::new()),
+            Value::Int(_) => List::Int(Vec::new()),
+            Value::UInt(_) => List::UInt(Vec::new()),
+            Value::Str(_) => List::Str(Vec::new()),
+            Value::Bytes(_) => List::Bytes(Vec::new()),
+            Value::List(_) => List::List(Vec::new()),
+            Value::Struct(_) => List::Struct(Vec::new()),
+            Value::Ext { .. } => {
+                let start = items[0].0;
+                return Err(self.error_at(start, "lists of extension values aren't supported"));
+            }
+        };
+        for (start, item) in items {
+            match (&mut list, item) {
+                (List::Bool(items), Value::Bool(val)) => items.push(val),
+                (List::F32(items), Value::F32(val)) => items.push(val),
+                (List::F64(items), Value::F64(val)) => items.push(val),
+                (List::Int(items), Value::Int(val)) => items.push(val),
+                (List::UInt(items), Value::UInt(val)) => items.push(val),
+                (List::Str(items), Value::Str(val)) => items.push(val),
+                (List::Bytes(items), Value::Bytes(val)) => items.push(val),
+                (List::List(items), Value::List(val)) => items.push(val),
+                (List::Struct(items), Value::Struct(val)) => items.push(val),
+                _ => {
+                    return Err(self.error_at(start, "list elements must all have the same type"));
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    /// Reads the element type that follows `[]`.
+    fn empty_list(&mut self) -> Result<List<'de>> {
+        Ok(match self.word() {
+            "bool" => List::Bool(Vec::new()),
+            "f32" => List::F32(Vec::new()),
+            "f64" => List::F64(Vec::new()),
+            "int" => List::Int(Vec::new()),
+            "uint" => List::UInt(Vec::new()),
+            "str" => List::Str(Vec::new()),
+            "bytes" => List::Bytes(Vec::new()),
+            "list" => List::List(Vec::new()),
+            "struct" => List::Struct(Vec::new()),
+            _ => return Err(self.error("expected the element type of the empty list")),
+        })
+    }
+}
+
+fn hex_decode(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
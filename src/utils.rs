@@ -1,4 +1,4 @@
-use crate::{Result, errors};
+use crate::{ErrorKind, Result};
 
 pub fn read_byte(buf: &mut &[u8]) -> Result<u8> {
     if !buf.is_empty() {
@@ -8,7 +8,7 @@ pub fn read_byte(buf: &mut &[u8]) -> Result<u8> {
             Ok(byte)
         }
     } else {
-        Err(errors::UnexpectedEof.into())
+        Err(ErrorKind::Eof.into())
     }
 }
 
@@ -24,6 +24,42 @@ pub fn read_bytes<'de>(reader: &mut &'de [u8], len: usize) -> Result<&'de [u8]>
             Ok(slice)
         }
     } else {
-        Err(errors::UnexpectedEof.into())
+        Err(ErrorKind::Eof.into())
     }
 }
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Writes `bytes` in standard base64 with padding.
+pub fn write_base64(out: &mut impl std::fmt::Write, bytes: &[u8]) -> std::fmt::Result {
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => out.write_char(BASE64[(n >> (18 - 6 * i) & 63) as usize] as char)?,
+                false => out.write_char('=')?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads standard base64, with or without padding.
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut bits, mut n) = (0u32, 0);
+    for ch in text.bytes() {
+        let digit = BASE64.iter().position(|&b| b == ch)? as u32;
+        bits = bits << 6 | digit;
+        n += 6;
+        if n >= 8 {
+            n -= 8;
+            out.push((bits >> n) as u8);
+        }
+    }
+    Some(out)
+}
@@ -1,4 +1,4 @@
-use crate::{errors, utils};
+use crate::ErrorKind;
 
 use super::Result;
 
@@ -51,20 +51,42 @@ impl<const N: usize> LEB128 for Leb128Buf<N> {
     }
 }
 
-pub fn read_unsigned(reader: &mut &[u8]) -> Result<u64> {
+/// Decodes a LEB128 integer from the bytes `next_byte` yields, rejecting
+/// encodings that don't fit in 64 bits.
+pub fn decode_unsigned(mut next_byte: impl FnMut() -> Result<u8>) -> Result<u64> {
     let mut result = 0;
     let mut shift = 0;
 
     loop {
-        let byte = utils::read_byte(reader)?;
+        let byte = next_byte()?;
+        // The tenth byte holds the 64th bit and nothing else.
+        if shift == 63 && byte > 1 {
+            return Err(ErrorKind::InvalidVarint.into());
+        }
         result |= ((byte & 0x7F) as u64) << shift; // low-order 7 bits of value
 
         if (byte & 0x80) == 0 {
             break Ok(result); // No continuation bit, end of LEB128
         }
-        if shift >= 64 {
-            return Err(errors::VarIntError.into());
-        }
         shift += 7;
     }
 }
+
+/// Decodes a header: the type in the low nibble and an id (a key or a list
+/// length) in the high one, where `0b1111` means a varint holding `id - 15`
+/// follows.
+pub fn decode_header(mut next_byte: impl FnMut() -> Result<u8>) -> Result<(u64, u8)> {
+    let byte = next_byte()?;
+
+    let ty = byte & 0b00001111;
+    let id = (byte >> 4) as u64;
+
+    let id = if id == 0b1111 {
+        decode_unsigned(next_byte)?
+            .checked_add(15)
+            .ok_or(ErrorKind::InvalidVarint)?
+    } else {
+        id
+    };
+    Ok((id, ty))
+}
@@ -191,6 +191,16 @@ fn test_all_types() {
     assert_eq!(all_types, new_all_types.unwrap());
 }
 
+#[test]
+fn test_all_types_single_pass() {
+    let all_types = Types::new();
+    let buf = all_types.to_bytes();
+
+    let mut reader = &buf[..];
+    assert_eq!(all_types, Types::parse(&mut reader).unwrap());
+    assert!(reader.is_empty());
+}
+
 // ---------------------------------------------------------------------------------------
 
 #[bench]
@@ -213,6 +223,31 @@ fn bench_parse_and_decode(b: &mut Bencher) {
     });
 }
 
+// Decodes each field as it's read, so it should come in under `bench_parse`
+// plus `bench_decode`, which `bench_parse_and_decode` pays for.
+#[bench]
+fn bench_single_pass_parse(b: &mut Bencher) {
+    let all_types = Types::new();
+    let buf = all_types.to_bytes();
+
+    b.iter(|| {
+        let res = Types::parse(&mut &buf[..]);
+        assert!(res.is_ok());
+    });
+}
+
+#[bench]
+fn bench_read_from(b: &mut Bencher) {
+    // Borrowed fields can't be read from a stream, so only the owned user.
+    let mut buf = Vec::new();
+    Types::new().user.encode(&mut buf).unwrap();
+
+    b.iter(|| {
+        let res = User::read_from(&buf[..]);
+        assert!(res.is_ok());
+    });
+}
+
 #[bench]
 fn bench_parse(b: &mut Bencher) {
     let all_types = Types::new();
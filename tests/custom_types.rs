@@ -1,4 +1,5 @@
 use cor::{ConvertFrom, Decoder, Encoder, IntoValue, Value, errors::ConvertError};
+use std::borrow::Cow;
 
 struct Bytes<'de> {
     data: &'de [u8],
@@ -6,14 +7,14 @@ struct Bytes<'de> {
 
 impl<'de> IntoValue<'de> for Bytes<'de> {
     fn to_value(&self) -> Value<'de> {
-        Value::Bytes(self.data)
+        Value::Bytes(Cow::Borrowed(self.data))
     }
 }
 
 impl<'de> ConvertFrom<&Value<'de>> for Bytes<'de> {
     fn convert_from(value: &Value<'de>) -> Result<Self, ConvertError> {
         match value {
-            Value::Bytes(data) => Ok(Bytes { data }),
+            Value::Bytes(Cow::Borrowed(data)) => Ok(Bytes { data }),
             _ => Err(ConvertError::from("expected `Bytes` type")),
         }
     }
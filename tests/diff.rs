@@ -0,0 +1,106 @@
+use cor::{Change, Encoder, Entries, Path, Segment, Value, entries, value};
+
+#[derive(Encoder)]
+struct Frame {
+    #[key = 1]
+    id: u64,
+    #[key = 2]
+    samples: Vec<f64>,
+    #[key = 3]
+    points: Vec<Point>,
+    #[key = 4]
+    label: Option<String>,
+}
+
+#[derive(Encoder)]
+struct Point {
+    #[key = 1]
+    x: i32,
+    #[key = 2]
+    y: i32,
+}
+
+fn frame(samples: Vec<f64>, y: i32, label: Option<&str>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    Frame {
+        id: 7,
+        samples,
+        points: vec![Point { x: 0, y: 0 }, Point { x: 1, y }],
+        label: label.map(Into::into),
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_diff_replayed_messages() {
+    let old = frame(vec![1.0, 2.0, 3.0], 1, Some("a"));
+    let new = frame(vec![1.0, 2.5], 2, None);
+    let old = Entries::parse(&mut &old[..]).unwrap();
+    let new = Entries::parse(&mut &new[..]).unwrap();
+
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.changes(),
+        [
+            Change::Changed {
+                path: Path::from(vec![Segment::Key(2), Segment::Index(1)]),
+                old: Value::F64(2.0),
+                new: Value::F64(2.5),
+            },
+            Change::Removed {
+                path: Path::from(vec![Segment::Key(2), Segment::Index(2)]),
+                value: Value::F64(3.0),
+            },
+            Change::Changed {
+                path: Path::from(vec![Segment::Key(3), Segment::Index(1), Segment::Key(2)]),
+                old: Value::Int(1),
+                new: Value::Int(2),
+            },
+            Change::Removed {
+                path: Path::from(vec![Segment::Key(4)]),
+                value: value!("a"),
+            },
+        ]
+    );
+    assert_eq!(
+        diff.to_string(),
+        "~ 2.[1]: 2.0 -> 2.5\n- 2.[2]: 3.0\n~ 3.[1].2: 1 -> 2\n- 4: \"a\""
+    );
+
+    assert!(old.diff(&old).is_empty());
+    assert_eq!(new.diff(&old).len(), diff.len());
+}
+
+#[test]
+fn test_diff_values() {
+    let old = value! { 1: [[1u], [2u]], 2: [{ 1: true }], 3: { 1: "x" }, 4: 1.0 };
+    let new = value! { 4: 1.0f, 1: [[1u], ["y"], []int], 3: {}, 2: [{ 1: true }, {}], 5: (1 2 3) };
+    let diff = old.diff(&new);
+    assert_eq!(
+        diff.to_string(),
+        [
+            r#"~ 1.[1]: [2u] -> ["y"]"#,
+            "+ 1.[2]: []int",
+            "+ 2.[1]: {}",
+            r#"- 3.1: "x""#,
+            "~ 4: 1.0 -> 1.0f",
+            r#"+ 5: x"010203""#,
+        ]
+        .join("\n")
+    );
+    assert_eq!(format!("{:.1}", diff.changes()[5]), r#"+ 5: x"01…+2""#);
+
+    // Field order doesn't matter, and NaN equals itself.
+    let mut a = entries! { 1: 1, 2: 0.0 };
+    let mut b = entries! { 2: 0.0, 1: 1 };
+    a.insert(3, Value::F64(f64::NAN));
+    b.insert(3, Value::F64(f64::NAN));
+    assert!(a.diff(&b).is_empty());
+
+    // Scalars at the root are changed as a whole.
+    let diff = value!(1).diff(&value!(2u));
+    assert_eq!(diff.to_string(), "~ 1 -> 2u");
+    assert!(diff.changes()[0].path().is_empty());
+}
@@ -0,0 +1,157 @@
+use cor::{DecodeOptions, Decoder, DuplicateKeys, Entries, EntriesRef, ErrorKind, Value, ValueRef};
+
+#[derive(Decoder, Debug, PartialEq)]
+struct Message {
+    #[key = 1]
+    value: u32,
+    #[key = 2]
+    ids: Vec<u32>,
+}
+
+// Field 1 set to 1 and then 2, field 2 holding `[1, 2]` and then `[3]`.
+const REPEATED: [u8; 12] = [
+    0x15, 0x01, 0x28, 0x25, 0x01, 0x02, 0x15, 0x02, 0x28, 0x15, 0x03, 0x0A,
+];
+
+fn options(duplicate_keys: DuplicateKeys) -> DecodeOptions {
+    DecodeOptions {
+        duplicate_keys,
+        ..Default::default()
+    }
+}
+
+fn message(policy: DuplicateKeys) -> cor::Result<Message> {
+    Message::parse_with(&mut &REPEATED[..], &options(policy))
+}
+
+fn entries(policy: DuplicateKeys) -> cor::Result<Entries<'static>> {
+    Entries::parse_with(&mut &REPEATED[..], &options(policy))
+}
+
+#[test]
+fn test_rejected_by_default() {
+    let err = Entries::parse(&mut &REPEATED[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 1 });
+    assert_eq!(err.path().to_string(), "1");
+
+    let err = Message::parse(&mut &REPEATED[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 1 });
+    assert_eq!(err.offset(), Some(7));
+    assert_eq!(err.to_string(), "duplicate key `1` at byte 7 (field `1`)");
+
+    let err = EntriesRef::parse(&mut &REPEATED[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 1 });
+}
+
+#[test]
+fn test_undeclared_struct_with_repeated_key() {
+    // Fields 1 and 2, then field 3, which `Message` doesn't declare, holding
+    // either a struct or a list of one struct with key 2 twice.
+    let nested = [0x15, 0x01, 0x28, 0x05, 0x39, 0x21, 0x21, 0x0A, 0x0A];
+    let listed = [0x15, 0x01, 0x28, 0x05, 0x38, 0x19, 0x21, 0x21, 0x0A, 0x0A];
+    for (buf, path) in [(&nested[..], "3.2"), (&listed[..], "3.[0].2")] {
+        let err = Message::parse(&mut &buf[..]).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+        assert_eq!(err.path().to_string(), path);
+        let err = Message::read_from(buf).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+        let err = Entries::parse_filtered(&mut &buf[..], |key| key != 3).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+        let err = EntriesRef::parse(&mut &buf[..]).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+
+        let message = Message::parse_with(&mut &buf[..], &options(DuplicateKeys::LastWins));
+        assert_eq!(message.unwrap().value, 1);
+    }
+}
+
+#[test]
+fn test_undeclared_key_repeated() {
+    // Fields 1 and 2, then field 3, which `Message` doesn't declare, twice.
+    let buf = [0x15, 0x01, 0x28, 0x05, 0x35, 0x01, 0x35, 0x02, 0x0A];
+    let err = Message::parse(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 3 });
+    assert_eq!(err.offset(), Some(7));
+    assert_eq!(
+        Entries::parse(&mut &buf[..]).unwrap_err().kind(),
+        err.kind()
+    );
+    let err = Message::read_from(&buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 3 });
+    let err = Entries::parse_filtered(&mut &buf[..], |key| key != 3).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 3 });
+
+    let expected = Message {
+        value: 1,
+        ids: vec![],
+    };
+    for policy in [DuplicateKeys::FirstWins, DuplicateKeys::LastWins] {
+        let message = Message::parse_with(&mut &buf[..], &options(policy));
+        assert_eq!(message.unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_first_wins() {
+    let expected = Message {
+        value: 1,
+        ids: vec![1, 2],
+    };
+    assert_eq!(message(DuplicateKeys::FirstWins).unwrap(), expected);
+    assert_eq!(
+        Message::decode(&entries(DuplicateKeys::FirstWins).unwrap()).unwrap(),
+        expected
+    );
+
+    let view = EntriesRef::parse_with(&mut &REPEATED[..], &options(DuplicateKeys::FirstWins));
+    assert!(matches!(
+        view.unwrap().get(1).unwrap(),
+        Some(ValueRef::UInt(1))
+    ));
+}
+
+#[test]
+fn test_last_wins() {
+    let expected = Message {
+        value: 2,
+        ids: vec![3],
+    };
+    assert_eq!(message(DuplicateKeys::LastWins).unwrap(), expected);
+
+    let entries = entries(DuplicateKeys::LastWins).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(Message::decode(&entries).unwrap(), expected);
+
+    let view = EntriesRef::parse_with(&mut &REPEATED[..], &options(DuplicateKeys::LastWins));
+    assert!(matches!(
+        view.unwrap().get(1).unwrap(),
+        Some(ValueRef::UInt(2))
+    ));
+}
+
+#[test]
+fn test_merge_lists() {
+    let expected = Message {
+        value: 2,
+        ids: vec![1, 2, 3],
+    };
+    assert_eq!(message(DuplicateKeys::MergeLists).unwrap(), expected);
+
+    let entries = entries(DuplicateKeys::MergeLists).unwrap();
+    assert!(matches!(entries.get(1), Some(Value::UInt(2))));
+    assert_eq!(Message::decode(&entries).unwrap(), expected);
+
+    // Views hold encoded bytes, which can't be merged.
+    let err = EntriesRef::parse_with(&mut &REPEATED[..], &options(DuplicateKeys::MergeLists))
+        .unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 1 });
+}
+
+#[test]
+fn test_nested_duplicates() {
+    // Field 0 holding a struct that repeats key 3.
+    let buf = [0x09, 0x35, 0x01, 0x35, 0x02, 0x0A, 0x0A];
+    let err = Entries::parse(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 3 });
+    assert_eq!(err.path().to_string(), "0.3");
+}
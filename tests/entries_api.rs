@@ -0,0 +1,118 @@
+use cor::{Decoder, Encoder, Entries, Value};
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Request {
+    #[key = 0]
+    version: u32,
+    #[key = 1]
+    user: String,
+    #[key = 2]
+    token: Option<String>,
+}
+
+fn request() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Request {
+        version: 1,
+        user: "alex".into(),
+        token: Some("secret".into()),
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_rewrite_message() {
+    let buf = request();
+    let mut entries = Entries::parse(&mut &buf[..]).unwrap();
+
+    // Redact a field and bump the version.
+    assert!(entries.remove(2).is_some());
+    entries
+        .entry(0)
+        .and_modify(|version| {
+            if let Value::UInt(version) = version {
+                *version += 1;
+            }
+        })
+        .or_insert(Value::UInt(1));
+
+    let mut out = Vec::new();
+    entries.encode(&mut out).unwrap();
+    assert_eq!(
+        Request::parse(&mut &out[..]).unwrap(),
+        Request {
+            version: 2,
+            user: "alex".into(),
+            token: None,
+        }
+    );
+}
+
+#[test]
+fn test_map_methods() {
+    let mut entries: Entries = [(3, Value::UInt(3)), (1, Value::Bool(true))]
+        .into_iter()
+        .collect();
+    entries.extend([(2, Value::Int(-2)), (3, Value::UInt(30))]);
+
+    // A repeated key replaces the value in place.
+    assert_eq!(entries.keys().collect::<Vec<_>>(), [3, 1, 2]);
+    assert!(entries.contains_key(2));
+    assert!(!entries.contains_key(4));
+    assert!(matches!(
+        entries.insert(1, Value::Bool(false)),
+        Some(Value::Bool(true))
+    ));
+
+    // Whatever was inserted encodes into a message the default policy accepts.
+    let mut buf = Vec::new();
+    entries.encode(&mut buf).unwrap();
+    assert_eq!(Entries::parse(&mut &buf[..]).unwrap(), entries);
+
+    assert!(matches!(entries.remove(3), Some(Value::UInt(30))));
+    assert!(!entries.contains_key(3));
+    assert_eq!(entries.len(), 2);
+
+    entries.retain(|key, _| key != 1);
+    assert_eq!(entries.keys().collect::<Vec<_>>(), [2]);
+
+    for (_, value) in &mut entries {
+        *value = Value::Int(-20);
+    }
+    assert!(matches!(entries.get(2), Some(Value::Int(-20))));
+
+    let fields: Vec<(u16, Value)> = entries.into_iter().collect();
+    assert!(matches!(fields[..], [(2, Value::Int(-20))]));
+}
+
+#[test]
+fn test_entry() {
+    let mut entries = Entries::new();
+    assert!(entries.entry(5).insert(Value::UInt(1)).is_none());
+    assert!(matches!(
+        entries.entry(5).insert(Value::UInt(2)),
+        Some(Value::UInt(1))
+    ));
+    assert_eq!(entries.len(), 1);
+
+    let value = entries.entry(6).or_insert_with(|| Value::Str("new".into()));
+    assert!(matches!(value, Value::Str(s) if s == "new"));
+    assert_eq!(entries.entry(6).key(), 6);
+    assert_eq!(
+        entries.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+        [5, 6]
+    );
+    assert_eq!(entries.values().count(), 2);
+}
+
+#[test]
+fn test_glob_import() {
+    use cor::*;
+
+    let entries = entries! { 1: "a", 2: { 1: 7u } };
+    let mut buf = Vec::new();
+    entries.encode(&mut buf).unwrap();
+    assert_eq!(Entries::parse(&mut &buf[..]).unwrap(), entries);
+}
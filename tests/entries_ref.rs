@@ -0,0 +1,157 @@
+use cor::{DecodeOptions, DuplicateKeys, Encoder, Entries, EntriesRef, ErrorKind, ValueRef};
+
+#[derive(Encoder)]
+struct Envelope {
+    #[key = 0]
+    route: String,
+    #[key = 1]
+    hops: Vec<u32>,
+    #[key = 2]
+    sender: Sender,
+    #[key = 3]
+    payload: Vec<u8>,
+    #[key = 4]
+    tags: Vec<Vec<String>>,
+}
+
+#[derive(Encoder)]
+struct Sender {
+    #[key = 0]
+    id: i64,
+    #[key = 1]
+    online: bool,
+}
+
+fn envelope() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Envelope {
+        route: "eu-west".into(),
+        hops: vec![3, 1, 4],
+        sender: Sender {
+            id: -7,
+            online: true,
+        },
+        payload: vec![0xAB; 100_000],
+        tags: vec![vec!["a".into()], vec!["b".into(), "c".into()]],
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_get_single_field() {
+    let buf = envelope();
+    let view = EntriesRef::parse(&mut &buf[..]).unwrap();
+    assert_eq!(view.len(), 5);
+    assert_eq!(view.keys().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+    let Some(ValueRef::Str(route)) = view.get(0).unwrap() else {
+        panic!("expected a string");
+    };
+    assert_eq!(route, "eu-west");
+    // Borrowed straight from the input.
+    assert!(buf.as_ptr_range().contains(&route.as_ptr()));
+
+    let Some(ValueRef::Bytes(payload)) = view.get(3).unwrap() else {
+        panic!("expected bytes");
+    };
+    assert_eq!(payload.len(), 100_000);
+    assert!(view.get(9).unwrap().is_none());
+}
+
+#[test]
+fn test_lazy_lists() {
+    let buf = envelope();
+    let view = EntriesRef::parse(&mut &buf[..]).unwrap();
+
+    let Some(ValueRef::List(hops)) = view.get(1).unwrap() else {
+        panic!("expected a list");
+    };
+    assert_eq!(hops.len(), 3);
+    let hops: Vec<u64> = hops
+        .iter()
+        .map(|hop| match hop.unwrap() {
+            ValueRef::UInt(hop) => hop,
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    assert_eq!(hops, [3, 1, 4]);
+
+    let Some(ValueRef::List(tags)) = view.get(4).unwrap() else {
+        panic!("expected a list");
+    };
+    let lens: Vec<usize> = tags
+        .into_iter()
+        .map(|inner| match inner.unwrap() {
+            ValueRef::List(inner) => inner.len(),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    assert_eq!(lens, [1, 2]);
+}
+
+#[test]
+fn test_nested_struct() {
+    let buf = envelope();
+    let view = EntriesRef::parse(&mut &buf[..]).unwrap();
+
+    let Some(ValueRef::Struct(sender)) = view.get(2).unwrap() else {
+        panic!("expected a struct");
+    };
+    assert!(matches!(sender.get(0).unwrap(), Some(ValueRef::Int(-7))));
+    assert!(matches!(sender.get(1).unwrap(), Some(ValueRef::Bool(true))));
+    assert_eq!(format!("{sender:?}"), "{0: Int(-7), 1: Bool(true)}");
+}
+
+#[test]
+fn test_nested_decoding_keeps_options() {
+    // Field 0 is a struct that repeats key 1.
+    let buf = [0x09, 0x11, 0x10, 10, 10];
+    let options = DecodeOptions {
+        duplicate_keys: DuplicateKeys::LastWins,
+        ..Default::default()
+    };
+    assert!(Entries::parse_with(&mut &buf[..], &options).is_ok());
+    let view = EntriesRef::parse_with(&mut &buf[..], &options).unwrap();
+    let Some(ValueRef::Struct(inner)) = view.get(0).unwrap() else {
+        panic!("expected a struct");
+    };
+    assert!(matches!(inner.get(1).unwrap(), Some(ValueRef::Bool(false))));
+
+    // 200 nested structs, deeper than the default limit.
+    let depth = 200;
+    let mut buf = vec![0x09; depth - 1];
+    buf.extend(std::iter::repeat_n(10, depth));
+    let options = DecodeOptions {
+        max_depth: 1000,
+        ..Default::default()
+    };
+    let mut view = EntriesRef::parse_with(&mut &buf[..], &options).unwrap();
+    for _ in 1..depth - 1 {
+        let Some(ValueRef::Struct(inner)) = view.get(0).unwrap() else {
+            panic!("expected a struct");
+        };
+        view = inner;
+    }
+    assert!(view.get(0).unwrap().is_some());
+}
+
+#[test]
+fn test_raw_field() {
+    let buf = envelope();
+    let view = EntriesRef::parse(&mut &buf[..]).unwrap();
+    // Length prefix followed by the string itself.
+    assert_eq!(view.raw(0).unwrap(), b"\x07eu-west");
+}
+
+#[test]
+fn test_malformed_message() {
+    let mut buf = envelope();
+    let pos = buf.windows(7).position(|w| w == b"eu-west").unwrap();
+    buf[pos] = 0xFF;
+
+    let err = EntriesRef::parse(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidUtf8);
+    assert_eq!(err.path().to_string(), "0");
+}
@@ -0,0 +1,118 @@
+use std::hash::{BuildHasher, RandomState};
+
+use cor::{Encoder, Entries, List, Total, TotalOrd, Value};
+
+#[derive(Encoder)]
+struct Point {
+    #[key = 0]
+    x: f64,
+    #[key = 1]
+    tags: Vec<String>,
+}
+
+fn point(x: f64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    Point {
+        x,
+        tags: vec!["a".into(), "b".into()],
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_partial_eq() {
+    let (a, b) = (point(1.5), point(1.5));
+    assert_eq!(
+        Entries::parse(&mut &a[..]).unwrap(),
+        Entries::parse(&mut &b[..]).unwrap()
+    );
+    assert_ne!(
+        Entries::parse(&mut &a[..]).unwrap(),
+        Entries::parse(&mut &point(2.5)[..]).unwrap()
+    );
+    assert_eq!(
+        Value::List(List::Str(vec!["a".into()])),
+        Value::List(List::Str(vec!["a".into()]))
+    );
+    assert_ne!(Value::UInt(1), Value::Int(1));
+
+    // IEEE 754 semantics: NaN is not equal to itself.
+    let nan = point(f64::NAN);
+    let nan = Entries::parse(&mut &nan[..]).unwrap();
+    assert_ne!(nan, nan.clone());
+    assert_eq!(Value::F64(0.0), Value::F64(-0.0));
+}
+
+#[test]
+fn test_total_order() {
+    assert_eq!(Total(Value::F64(f64::NAN)), Total(Value::F64(f64::NAN)));
+    assert_ne!(Total(Value::F64(0.0)), Total(Value::F64(-0.0)));
+    assert!(Total(Value::Bool(true)) < Total(Value::Int(-1)));
+    assert!(Total(Value::Int(-1)) < Total(Value::Int(0)));
+    assert!(Total(List::UInt(vec![1])) < Total(List::UInt(vec![1, 0])));
+
+    let nan = point(f64::NAN);
+    let nan = Entries::parse(&mut &nan[..]).unwrap();
+    let state = RandomState::new();
+    assert_eq!(
+        state.hash_one(Total(&nan)),
+        state.hash_one(Total(&nan.clone()))
+    );
+    let mut items = vec![Total(nan.clone()), Total(nan)];
+    items.dedup();
+    assert_eq!(items.len(), 1);
+
+    let values = [
+        Value::Str("b".into()),
+        Value::Str("a".into()),
+        Value::F32(1.0),
+    ];
+    let mut sorted: Vec<_> = values.iter().map(Total).collect();
+    sorted.sort();
+    let sorted: Vec<_> = sorted.into_iter().map(|Total(value)| value).collect();
+    assert_eq!(sorted, [&values[2], &values[1], &values[0]]);
+}
+
+/// Deduplicates any values `Total` can wrap, keeping the first of each.
+fn dedup_total<T: TotalOrd>(items: Vec<T>) -> Vec<T> {
+    let mut out: Vec<Total<T>> = Vec::new();
+    for item in items.into_iter().map(Total) {
+        if !out.contains(&item) {
+            out.push(item);
+        }
+    }
+    out.into_iter().map(|Total(item)| item).collect()
+}
+
+#[test]
+fn test_total_ord_bound() {
+    let values = vec![Value::F64(f64::NAN), Value::F64(f64::NAN), Value::Int(1)];
+    assert_eq!(dedup_total(values).len(), 2);
+    let lists = vec![List::UInt(vec![1]), List::UInt(vec![1])];
+    assert_eq!(dedup_total(lists).len(), 1);
+}
+
+#[test]
+fn test_eq_unordered() {
+    let mut a = Entries::new();
+    a.insert(1, Value::UInt(1));
+    a.insert(2, Value::Str("two".into()));
+    let mut b = Entries::new();
+    b.insert(2, Value::Str("two".into()));
+    b.insert(1, Value::UInt(1));
+
+    assert_ne!(a, b);
+    assert!(a.eq_unordered(&b));
+
+    let nested_a = Value::List(List::Struct(vec![a.clone()]));
+    let nested_b = Value::List(List::Struct(vec![b.clone()]));
+    assert!(nested_a.eq_unordered(&nested_b));
+
+    b.insert(3, Value::Bool(false));
+    assert!(!a.eq_unordered(&b));
+    assert!(
+        !Value::List(List::UInt(vec![1, 2])).eq_unordered(&Value::List(List::UInt(vec![2, 1])))
+    );
+}
@@ -0,0 +1,111 @@
+use cor::{Decoder, Encoder, Entries, ErrorKind, List, Path, Segment, Value};
+
+#[derive(Encoder, Decoder, Debug)]
+struct Outer {
+    #[key = 21]
+    user: User,
+}
+
+#[derive(Encoder, Decoder, Debug)]
+struct User {
+    #[key = 0]
+    id: Vec<u16>,
+    #[key = 1]
+    name: String,
+}
+
+fn outer_bytes() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Outer {
+        user: User {
+            id: vec![1, 2, 3],
+            name: "alex".into(),
+        },
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_eof_offset_and_path() {
+    let buf = outer_bytes();
+    // Cut inside the `name` string of the nested user.
+    let truncated = &buf[..buf.len() - 4];
+
+    for err in [
+        Entries::parse(&mut &truncated[..]).unwrap_err(),
+        Outer::parse(&mut &truncated[..]).unwrap_err(),
+        Entries::read_from(truncated).unwrap_err(),
+    ] {
+        assert_eq!(err.kind(), &ErrorKind::Eof);
+        assert_eq!(err.path().segments(), [Segment::Key(21), Segment::Key(1)]);
+        assert_eq!(err.path().to_string(), "21.1");
+        assert!(
+            err.to_string()
+                .starts_with("unexpected end of file at byte")
+        );
+    }
+    // Reported where the truncated `name` payload starts.
+    let err = Entries::parse(&mut &truncated[..]).unwrap_err();
+    assert_eq!(err.offset(), Some(truncated.len() - 2));
+}
+
+#[test]
+fn test_invalid_utf8_offset() {
+    let mut buf = outer_bytes();
+    let pos = buf.windows(4).position(|w| w == b"alex").unwrap();
+    buf[pos + 2] = 0xFF;
+
+    let err = Outer::parse(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidUtf8);
+    assert_eq!(err.offset(), Some(pos + 2));
+    assert_eq!(err.path().to_string(), "21.1");
+
+    let source = std::error::Error::source(&err).unwrap();
+    assert!(source.is::<std::str::Utf8Error>());
+}
+
+#[test]
+fn test_list_element_path() {
+    // Field 0 holding a list of two bools, the second of which is invalid.
+    let buf = [0x08, 0x21, 0x01, 0x07, 0x0A];
+    let err = Entries::parse(&mut &buf[..]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidBool { value: 7 });
+    assert_eq!(err.offset(), Some(3));
+    assert_eq!(
+        err.to_string(),
+        "invalid boolean value: `7` at byte 3 (field `0.[1]`)"
+    );
+}
+
+#[test]
+fn test_conversion_path() {
+    let mut user = Entries::new();
+    user.insert(0, Value::List(List::UInt(vec![1, 2, 3, 70_000])));
+    user.insert(1, Value::Str("alex".into()));
+    let mut outer = Entries::new();
+    outer.insert(21, Value::Struct(user));
+
+    let err = Outer::decode(&outer).unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Conversion { .. }));
+    assert_eq!(err.offset(), None);
+    assert_eq!(
+        *err.path(),
+        Path::from(vec![Segment::Key(21), Segment::Key(0), Segment::Index(3)])
+    );
+    assert!(
+        err.to_string()
+            .starts_with("conversion error for key `21.0.[3]`:")
+    );
+}
+
+#[test]
+fn test_custom_error_source() {
+    use std::error::Error as _;
+
+    let err = cor::Error::custom("checksum mismatch");
+    assert!(matches!(err.kind(), ErrorKind::Conversion { .. }));
+    assert_eq!(err.to_string(), "custom conversion failed");
+    assert_eq!(err.source().unwrap().to_string(), "checksum mismatch");
+}
@@ -0,0 +1,42 @@
+use cor::{Decoder, Encoder, Entries, ErrorKind};
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Ping {
+    #[key = 1]
+    seq: u64,
+}
+
+fn ping(seq: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    Ping { seq }.encode(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_exact_input() {
+    let buf = ping(3);
+    assert_eq!(Ping::from_slice_exact(&buf).unwrap(), Ping { seq: 3 });
+    assert_eq!(Entries::from_slice_exact(&buf).unwrap().len(), 1);
+}
+
+#[test]
+fn test_trailing_bytes() {
+    let mut buf = ping(3);
+    buf.extend(ping(4));
+
+    let err = Ping::from_slice_exact(&buf).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::TrailingBytes { count: 3 });
+    assert_eq!(
+        Entries::from_slice_exact(&buf).unwrap_err().to_string(),
+        "3 trailing bytes after the end of the message"
+    );
+
+    // The non-exact entry point still stops at the end marker.
+    assert!(Ping::parse(&mut &buf[..]).is_ok());
+}
+
+#[test]
+fn test_truncated_input() {
+    let buf = ping(3);
+    assert!(Ping::from_slice_exact(&buf[..buf.len() - 1]).is_err());
+}
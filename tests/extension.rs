@@ -0,0 +1,89 @@
+use cor::{Decoder, Encoder, Entries, Ext, Extension, Value, errors::ConvertError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Extension for Point {
+    const TAG: u64 = 7;
+
+    fn encode_ext(&self) -> Vec<u8> {
+        let mut buf = self.x.to_le_bytes().to_vec();
+        buf.extend_from_slice(&self.y.to_le_bytes());
+        buf
+    }
+
+    fn decode_ext(data: &[u8]) -> Result<Self, ConvertError> {
+        let data: [u8; 8] = data
+            .try_into()
+            .map_err(|_| ConvertError::from("expected 8 bytes"))?;
+        Ok(Point {
+            x: i32::from_le_bytes(data[..4].try_into().unwrap()),
+            y: i32::from_le_bytes(data[4..].try_into().unwrap()),
+        })
+    }
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Place {
+    #[key = 1]
+    name: String,
+    #[key = 2]
+    location: Ext<Point>,
+}
+
+#[test]
+fn test_extension_roundtrip() {
+    let place = Place {
+        name: "home".into(),
+        location: Ext(Point { x: -3, y: 42 }),
+    };
+
+    let mut buf = Vec::new();
+    place.encode(&mut buf).unwrap();
+
+    let entries = Entries::parse(&mut &buf[..]).unwrap();
+    match entries.get(2) {
+        Some(Value::Ext { tag, data }) => {
+            assert_eq!(*tag, Point::TAG);
+            assert_eq!(data.len(), 8);
+        }
+        _ => panic!("expected extension value"),
+    }
+    assert_eq!(Place::decode(&entries).unwrap(), place);
+}
+
+#[test]
+fn test_extension_tag_mismatch() {
+    let mut entries = Entries::new();
+    entries.insert(1, Value::Str("home".into()));
+    entries.insert(
+        2,
+        Value::Ext {
+            tag: 8,
+            data: vec![0; 8].into(),
+        },
+    );
+    assert!(Place::decode(&entries).is_err());
+}
+
+#[test]
+fn test_extension_print() {
+    let point = Point { x: 1, y: 2 }.encode_ext();
+    let unknown = Value::Ext {
+        tag: 99,
+        data: vec![1, 2].into(),
+    };
+    assert_eq!(format!("{unknown:?}"), "#99(1 2)");
+
+    cor::register_extension::<Point>();
+    let known = Value::Ext {
+        tag: Point::TAG,
+        data: point.into(),
+    };
+    assert_eq!(format!("{known:?}"), "#7(Point { x: 1, y: 2 })");
+    assert_eq!(known.to_string(), "#7(Point { x: 1, y: 2 })");
+    assert_eq!(known.to_text(), "#7(1 0 0 0 2 0 0 0)");
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "json")]
+
+use cor::{Encoder, Entries, List, Value, json, value};
+use serde_json::json;
+
+#[derive(Encoder)]
+struct Event {
+    #[key = 1]
+    id: u64,
+    #[key = 2]
+    delta: i32,
+    #[key = 3]
+    ratio: f32,
+    #[key = 4]
+    payload: Vec<u8>,
+    #[key = 5]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_stream_parsed_message() {
+    let mut buf = Vec::new();
+    Event {
+        id: u64::MAX,
+        delta: -3,
+        ratio: 0.1,
+        payload: vec![0, 1, 2, 255],
+        tags: vec!["a\"b".into()],
+    }
+    .encode(&mut buf)
+    .unwrap();
+    let entries = Entries::parse(&mut &buf[..]).unwrap();
+
+    let mut out = Vec::new();
+    json::to_writer(&mut out, &entries).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(
+        out,
+        r#"{"1":18446744073709551615,"2":-3,"3":0.1,"4":"AAEC/w==","5":["a\"b"]}"#
+    );
+    assert_eq!(
+        json::to_value(&entries),
+        json!({ "1": u64::MAX, "2": -3, "3": 0.1, "4": "AAEC/w==", "5": ["a\"b"] })
+    );
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&out).unwrap(),
+        json::to_value(&entries)
+    );
+}
+
+#[test]
+fn test_write_edge_cases() {
+    let value = value! { 1: [1.0f, 2.5f], 2: #7(1 2 3), 3: [[]int, [true]], 4: {} };
+    assert_eq!(
+        json::to_string(&value),
+        r#"{"1":[1.0,2.5],"2":{"tag":7,"data":"AQID"},"3":[[],[true]],"4":{}}"#
+    );
+    assert_eq!(json::to_string(&Value::F64(f64::NAN)), "null");
+    assert_eq!(json::to_value(&Value::F32(f32::INFINITY)), json!(null));
+    assert_eq!(json::to_string(&Value::F64(1e300)), "1e300");
+}
+
+#[test]
+fn test_read() {
+    let value = json::from_str(
+        r#"{"1": 5, "2": -5, "3": 1.5, "4": "s", "5": [1, -2], "6": [1, 2.5],
+            "7": [{"1": true}], "8": {"tag": 7, "data": "AQID"}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        value,
+        value! {
+            1: 5u, 2: -5, 3: 1.5, 4: "s", 5: [1, -2], 6: [1.0, 2.5],
+            7: [{ 1: true }], 8: #7(1 2 3),
+        }
+    );
+    assert_eq!(
+        json::from_value(json!([])).unwrap(),
+        Value::List(List::Int(Vec::new()))
+    );
+
+    // Round trip through JSON keeps everything JSON can tell apart.
+    let original = value! { 1: 5u, 2: -5, 3: 0.25, 4: ["x", "y"], 5: { 9: [[1u]] } };
+    let json = json::to_string(&original);
+    assert_eq!(json::from_str(&json).unwrap(), original);
+}
+
+#[test]
+fn test_read_errors() {
+    let err = json::from_value(json!({ "1": { "2": null } })).unwrap_err();
+    assert_eq!(err.path().to_string(), "1.2");
+    assert!(json::from_value(json!({ "name": 1 })).is_err());
+    assert!(json::from_value(json!([1, "a"])).is_err());
+    assert!(json::from_value(json!({ "70000": 1 })).is_err());
+    assert!(json::from_str("{").is_err());
+}
@@ -0,0 +1,163 @@
+use cor::{DecodeOptions, Decoder, Encoder, Entries, ErrorKind};
+
+#[derive(Decoder, Debug)]
+struct Flat {
+    #[key = 1]
+    _id: Option<u32>,
+}
+
+/// `depth` structs, each holding the next one in field 0.
+fn nested_structs(depth: usize) -> Vec<u8> {
+    let mut buf = vec![0x09; depth - 1];
+    buf.extend(std::iter::repeat_n(10, depth));
+    buf
+}
+
+/// A struct whose field 0 holds `depth - 1` lists, each holding the next one.
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut buf = vec![0x08];
+    buf.extend(std::iter::repeat_n(0x18, depth - 2));
+    buf.extend([0x08, 10]); // innermost list is empty
+    buf
+}
+
+fn is_depth_error(err: &cor::Error) -> bool {
+    matches!(err.kind(), ErrorKind::DepthLimitExceeded { .. })
+}
+
+#[test]
+fn test_hostile_nesting() {
+    for buf in [nested_structs(100_000), nested_lists(100_000)] {
+        assert!(is_depth_error(&Entries::parse(&mut &buf[..]).unwrap_err()));
+        assert!(is_depth_error(&Flat::parse(&mut &buf[..]).unwrap_err()));
+        assert!(is_depth_error(&Entries::read_from(&buf[..]).unwrap_err()));
+    }
+}
+
+#[test]
+fn test_nesting_within_limit() {
+    let max_depth = DecodeOptions::DEFAULT_MAX_DEPTH;
+    let buf = nested_structs(max_depth);
+    assert!(Entries::parse(&mut &buf[..]).is_ok());
+    assert!(Flat::parse(&mut &buf[..]).is_ok());
+
+    let buf = nested_structs(max_depth + 1);
+    assert!(is_depth_error(&Entries::parse(&mut &buf[..]).unwrap_err()));
+}
+
+#[test]
+fn test_custom_max_depth() {
+    let options = DecodeOptions {
+        max_depth: 3,
+        ..Default::default()
+    };
+    let buf = nested_lists(3);
+    assert!(Entries::parse_with(&mut &buf[..], &options).is_ok());
+    assert!(Entries::read_from_with(&buf[..], &options).is_ok());
+
+    let buf = nested_lists(4);
+    let err = Flat::parse_with(&mut &buf[..], &options).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "nesting exceeds the maximum depth of 3 at byte 3 (field `0.[0].[0]`)"
+    );
+}
+
+#[derive(Encoder, Decoder, Debug)]
+struct Batch {
+    #[key = 1]
+    ids: Vec<u32>,
+    #[key = 2]
+    name: String,
+}
+
+fn batch(len: u32, name: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    Batch {
+        ids: (0..len).collect(),
+        name: name.into(),
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+fn exceeded(err: &cor::Error) -> &'static str {
+    match err.kind() {
+        ErrorKind::LimitExceeded { limit, .. } => limit,
+        kind => panic!("unexpected error: {kind:?}"),
+    }
+}
+
+#[test]
+fn test_huge_list_length_prefix() {
+    // Field 0 holding a list of unsigned integers that claims 4 billion elements.
+    let mut buf = vec![0x08, 0xF5];
+    buf.extend([0xF1, 0xFF, 0xFF, 0xFF, 0x0E]);
+    for err in [
+        Entries::parse(&mut &buf[..]).unwrap_err(),
+        Batch::parse(&mut &buf[..]).unwrap_err(),
+    ] {
+        assert_eq!(err.kind(), &ErrorKind::Eof);
+    }
+}
+
+#[test]
+fn test_max_list_len() {
+    let options = DecodeOptions {
+        max_list_len: 10,
+        ..Default::default()
+    };
+    assert!(Batch::parse_with(&mut &batch(10, "")[..], &options).is_ok());
+
+    let buf = batch(11, "");
+    let err = Entries::parse_with(&mut &buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_list_len");
+    assert_eq!(
+        err.kind().to_string(),
+        "`max_list_len` limit of 10 exceeded"
+    );
+    let err = Entries::read_from_with(&buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_list_len");
+
+    // A stream declaring four billion `f64`s is rejected at the list header,
+    // before any element is read.
+    let stream = [0x18, 0xF3, 0xF1, 0xCF, 0xAC, 0xF3, 0x0E];
+    let err = Entries::read_from_with(&stream[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_list_len");
+    assert_eq!(err.offset(), Some(stream.len()));
+}
+
+#[test]
+fn test_max_elements() {
+    let options = DecodeOptions {
+        max_elements: 12,
+        ..Default::default()
+    };
+    // Two fields plus ten list elements.
+    assert!(Entries::parse_with(&mut &batch(10, "")[..], &options).is_ok());
+
+    let buf = batch(11, "");
+    let err = Batch::parse_with(&mut &buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_elements");
+    assert!(Entries::read_from_with(&batch(10, "")[..], &options).is_ok());
+    let err = Entries::read_from_with(&buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_elements");
+}
+
+#[test]
+fn test_max_bytes() {
+    let buf = batch(0, "0123456789");
+    let options = DecodeOptions {
+        max_bytes: buf.len(),
+        ..Default::default()
+    };
+    assert!(Entries::parse_with(&mut &buf[..], &options).is_ok());
+    assert!(Entries::read_from_with(&buf[..], &options).is_ok());
+
+    let buf = batch(0, "0123456789a");
+    let err = Batch::parse_with(&mut &buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_bytes");
+    let err = Entries::read_from_with(&buf[..], &options).unwrap_err();
+    assert_eq!(exceeded(&err), "max_bytes");
+}
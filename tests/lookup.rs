@@ -0,0 +1,76 @@
+use cor::{Entries, Value};
+
+fn uint(value: Option<&Value>) -> Option<u64> {
+    match value {
+        Some(Value::UInt(val)) => Some(*val),
+        _ => None,
+    }
+}
+
+/// Keys `0..len` in a scrambled order, each storing its own key as the value.
+fn scrambled(len: u16) -> Entries<'static> {
+    let mut entries = Entries::new();
+    for i in 0..len {
+        let key = (i * 37) % len;
+        entries.insert(key, Value::UInt(key.into()));
+    }
+    entries
+}
+
+#[test]
+fn test_lookup_large_struct() {
+    let entries = scrambled(211);
+    for key in 0..211 {
+        assert_eq!(uint(entries.get(key)), Some(key.into()));
+    }
+    assert!(entries.get(211).is_none());
+    assert!(entries.get(u16::MAX).is_none());
+}
+
+#[test]
+fn test_insert_replaces() {
+    let mut entries = scrambled(100);
+    assert_eq!(uint(entries.insert(5, Value::UInt(500)).as_ref()), Some(5));
+    assert_eq!(uint(entries.get(5)), Some(500));
+    assert_eq!(entries.len(), 100);
+
+    let mut small = Entries::new();
+    assert!(small.insert(1, Value::UInt(1)).is_none());
+    assert_eq!(uint(small.insert(1, Value::UInt(2)).as_ref()), Some(1));
+    assert_eq!(uint(small.get(1)), Some(2));
+    assert_eq!(small.len(), 1);
+}
+
+#[test]
+fn test_insert_after_lookup() {
+    let mut entries = scrambled(50);
+    assert!(entries.get(60).is_none());
+    entries.insert(60, Value::UInt(60));
+    assert_eq!(uint(entries.get(60)), Some(60));
+
+    *entries.get_mut(60).unwrap() = Value::UInt(61);
+    assert_eq!(uint(entries.get(60)), Some(61));
+
+    let owned = entries.clone().into_owned();
+    assert_eq!(uint(owned.get(60)), Some(61));
+    assert_eq!(uint(entries.as_borrowed().get(49)), Some(49));
+}
+
+#[test]
+fn test_interleaved_insert_and_lookup() {
+    let mut entries = Entries::new();
+    for i in 0..2000u16 {
+        let key = i.wrapping_mul(40503);
+        entries.insert(key, Value::UInt(key.into()));
+        assert_eq!(uint(entries.get(key)), Some(key.into()));
+        entries.insert(key, Value::UInt(0));
+        assert_eq!(uint(entries.get(key)), Some(0));
+        entries.insert(key, Value::UInt(key.into()));
+    }
+    for i in 0..2000u16 {
+        let key = i.wrapping_mul(40503);
+        assert_eq!(uint(entries.get(key)), Some(key.into()));
+    }
+    entries.extend([(1, Value::UInt(1))]);
+    assert!(entries.contains_key(1));
+}
@@ -0,0 +1,72 @@
+use cor::{Decoder, Encoder, Entries, Value};
+use std::borrow::Cow;
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Message<'a> {
+    #[key = 1]
+    name: &'a str,
+    #[key = 2]
+    tags: Vec<&'a str>,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct OwnedMessage {
+    #[key = 1]
+    name: String,
+    #[key = 2]
+    tags: Vec<String>,
+}
+
+fn encode(message: &impl Encoder) -> Vec<u8> {
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_owned_outlives_buffer() {
+    let buf = encode(&Message {
+        name: "cor",
+        tags: vec!["a", "b"],
+    });
+    let entries: Entries<'static> = Entries::parse(&mut &buf[..]).unwrap().into_owned();
+    drop(buf);
+
+    let handle = std::thread::spawn(move || OwnedMessage::decode(&entries).unwrap());
+    assert_eq!(
+        handle.join().unwrap(),
+        OwnedMessage {
+            name: "cor".into(),
+            tags: vec!["a".into(), "b".into()],
+        }
+    );
+}
+
+#[test]
+fn test_owned_and_borrowed() {
+    let buf = encode(&Message {
+        name: "cor",
+        tags: vec!["a"],
+    });
+    let owned = Entries::parse(&mut &buf[..]).unwrap().into_owned();
+
+    // Borrowed targets can't be decoded from owned data...
+    assert!(Message::decode(&owned).is_err());
+
+    // ...but can from a borrowed view of it.
+    let view = owned.as_borrowed();
+    assert_eq!(
+        Message::decode(&view).unwrap(),
+        Message {
+            name: "cor",
+            tags: vec!["a"],
+        }
+    );
+    assert!(matches!(
+        view.get(1),
+        Some(Value::Str(Cow::Borrowed("cor")))
+    ));
+
+    // Owned values encode to the same bytes.
+    assert_eq!(encode(&OwnedMessage::decode(&owned).unwrap()), buf);
+}
@@ -0,0 +1,98 @@
+use cor::Segment::{Index, Key};
+use cor::{Entries, List, Path, Segment, Value};
+
+fn message() -> Entries<'static> {
+    let mut item = Entries::new();
+    item.insert(0, Value::Str("tea".into()));
+    item.insert(1, Value::UInt(3));
+
+    let mut user = Entries::new();
+    user.insert(0, Value::List(List::UInt(vec![1, 2, 3])));
+    user.insert(1, Value::List(List::Struct(vec![Entries::new(), item])));
+    user.insert(
+        2,
+        Value::List(List::List(vec![List::Struct(vec![Entries::new()])])),
+    );
+
+    let mut outer = Entries::new();
+    outer.insert(21, Value::Struct(user));
+    outer
+}
+
+#[test]
+fn test_pointer() {
+    let message = message();
+    assert!(matches!(message.pointer("/21/1/1/1"), Some(Value::UInt(3))));
+    assert!(matches!(message.pointer("/21/0"), Some(Value::List(_))));
+    assert!(matches!(message.pointer("/21"), Some(Value::Struct(_))));
+
+    let user = message.get(21).unwrap();
+    assert!(matches!(user.pointer("/1/1/0"), Some(Value::Str(s)) if s == "tea"));
+    assert!(matches!(user.pointer(""), Some(Value::Struct(_))));
+}
+
+#[test]
+fn test_pointer_misses() {
+    let message = message();
+    for pointer in [
+        "",
+        "21",
+        "/22",
+        "/21/1/2/0",
+        "/21/1/1/9",
+        "/21/x",
+        // Unboxed list elements and lists themselves aren't values.
+        "/21/0/1",
+        "/21/2/0",
+        "/21/2/0/0",
+        // Scalars have no children.
+        "/21/1/1/1/0",
+    ] {
+        assert!(message.pointer(pointer).is_none(), "{pointer}");
+    }
+}
+
+#[test]
+fn test_pointer_mut() {
+    let mut message = message();
+    *message.pointer_mut("/21/1/1/1").unwrap() = Value::UInt(4);
+    assert!(matches!(message.pointer("/21/1/1/1"), Some(Value::UInt(4))));
+
+    let mut value = Value::Struct(message);
+    if let Some(Value::Str(name)) = value.pointer_mut("/21/1/1/0") {
+        name.to_mut().push_str(" time");
+    }
+    assert!(matches!(value.pointer("/21/1/1/0"), Some(Value::Str(s)) if s == "tea time"));
+    assert!(value.pointer_mut("/21/0/0").is_none());
+}
+
+#[test]
+fn test_get_path() {
+    let message = message();
+    let path = |segments: &[Segment]| Path::from(segments.to_vec());
+    let tea = path(&[Key(21), Key(1), Index(1), Key(0)]);
+    assert!(matches!(message.get_path(&tea), Some(Value::Str(s)) if s == "tea"));
+    assert!(matches!(
+        message.get_path(&path(&[Key(21), Key(0)])),
+        Some(Value::List(_))
+    ));
+
+    let value = Value::Struct(message.clone());
+    assert!(matches!(value.get_path(&tea), Some(Value::Str(s)) if s == "tea"));
+    assert!(matches!(
+        value.get_path(&Path::new()),
+        Some(Value::Struct(_))
+    ));
+
+    for segments in [
+        &[Key(22)][..],
+        // A key doesn't index a list, nor an index select a field.
+        &[Key(21), Key(1), Key(1)],
+        &[Index(21)],
+        // List elements aren't values, as with `pointer`.
+        &[Key(21), Key(0), Index(1)],
+        &[Key(21), Key(1), Index(1)],
+    ] {
+        assert!(message.get_path(&path(segments)).is_none(), "{segments:?}");
+    }
+}
@@ -0,0 +1,79 @@
+use cor::{BytesFormat, Printer, Value, value};
+
+fn sample() -> Value<'static> {
+    value! {
+        1: "name",
+        2: 42u,
+        3: [1.5f, 2.0f],
+        4: { 1: true, 2: (0 1 255) },
+        5: [{ 1: -7 }, {}],
+    }
+}
+
+#[test]
+fn test_display_compact() {
+    assert_eq!(
+        sample().to_string(),
+        r#"{1: "name", 2: 42u, 3: [1.5f, 2.0f], 4: {1: true, 2: x"0001ff"}, 5: [{1: -7}, {}]}"#
+    );
+    assert_eq!(value!((1 2 3 4)).to_string(), r#"x"01020304""#);
+    assert_eq!(format!("{:.2}", value!((1 2 3 4))), r#"x"0102…+2""#);
+    assert_eq!(value!(#5(1 2)).to_string(), r#"#5(x"0102")"#);
+}
+
+#[test]
+fn test_display_pretty() {
+    let expected = r#"{
+    1: "name",
+    2: 42u,
+    3: [1.5f, 2.0f],
+    4: {
+        1: true,
+        2: x"0001ff",
+    },
+    5: [
+        {
+            1: -7,
+        },
+        {},
+    ],
+}"#;
+    assert_eq!(format!("{:#}", sample()), expected);
+
+    // Pretty output is valid text format once bytes are decimal.
+    let printer = Printer {
+        pretty: true,
+        bytes: BytesFormat::Decimal,
+        ..Printer::default()
+    };
+    let text = printer.display(&sample()).to_string();
+    assert_eq!(Value::from_text(&text).unwrap(), sample());
+}
+
+#[test]
+fn test_printer_options() {
+    let printer = Printer {
+        bytes: BytesFormat::Base64,
+        max_bytes: Some(4),
+        annotate_types: true,
+        ..Printer::default()
+    };
+    let value = value! { 1: b"hello world", 2: [1u, 2u], 3: 0.5, 4: "s", 5: []int };
+    assert_eq!(
+        printer.display(&value).to_string(),
+        r#"{1: bytes(b64"aGVsbA==…+7"), 2: [uint(1), uint(2)], 3: f64(0.5), 4: str("s"), 5: []int}"#
+    );
+
+    let Value::Struct(entries) = value else {
+        unreachable!()
+    };
+    let printer = Printer {
+        bytes: BytesFormat::Decimal,
+        max_bytes: Some(2),
+        ..Printer::default()
+    };
+    assert_eq!(
+        printer.display(&entries).to_string(),
+        r#"{1: (104 101 …+9), 2: [1u, 2u], 3: 0.5, 4: "s", 5: []int}"#
+    );
+}
@@ -0,0 +1,78 @@
+use cor::{DecodeOptions, Decoder, Encoder, Entries, ErrorKind, Value};
+
+#[derive(Encoder)]
+struct Full {
+    #[key = 1]
+    id: u32,
+    #[key = 2]
+    body: String,
+    #[key = 3]
+    attachments: Vec<String>,
+    #[key = 4]
+    flags: Vec<bool>,
+}
+
+#[derive(Decoder, Debug, PartialEq)]
+struct Summary {
+    #[key = 1]
+    id: u32,
+    #[key = 4]
+    flags: Vec<bool>,
+}
+
+fn full() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Full {
+        id: 7,
+        body: "hello".into(),
+        attachments: vec!["x".repeat(1024); 8],
+        flags: vec![true, false],
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_parse_filtered() {
+    let buf = full();
+    let entries = Entries::parse_filtered(&mut &buf[..], |key| [1, 4].contains(&key)).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(matches!(entries.get(1), Some(Value::UInt(7))));
+    assert!(entries.get(2).is_none());
+    assert!(entries.get(3).is_none());
+
+    let entries = Entries::parse_filtered(&mut &buf[..], |_| false).unwrap();
+    assert!(entries.is_empty());
+}
+
+#[test]
+fn test_skipped_fields_are_validated() {
+    let mut buf = full();
+    let pos = buf.windows(5).position(|w| w == b"hello").unwrap();
+    buf[pos] = 0xFF;
+
+    let err = Entries::parse_filtered(&mut &buf[..], |key| key == 1).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidUtf8);
+    assert_eq!(err.path().to_string(), "2");
+}
+
+#[test]
+fn test_read_from_filtered() {
+    let buf = full();
+    let options = DecodeOptions::default();
+    let entries = Entries::read_from_filtered_with(&buf[..], &options, |key| key == 2).unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries.get_and_convert::<String>(2).unwrap(), "hello");
+}
+
+#[test]
+fn test_derive_projects_declared_keys() {
+    let buf = full();
+    let expected = Summary {
+        id: 7,
+        flags: vec![true, false],
+    };
+    assert_eq!(Summary::parse(&mut &buf[..]).unwrap(), expected);
+    assert_eq!(Summary::read_from(&buf[..]).unwrap(), expected);
+}
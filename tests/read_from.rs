@@ -0,0 +1,82 @@
+use cor::{Decoder, Encoder, Entries, Value};
+use std::io::{Cursor, Read};
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Event {
+    #[key = 1]
+    name: String,
+    #[key = 2]
+    samples: Vec<Vec<f64>>,
+    #[key = 20]
+    payload: Vec<u8>,
+    #[key = 3]
+    source: Option<Source>,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Source {
+    #[key = 1]
+    host: String,
+}
+
+fn event(name: &str) -> Event {
+    Event {
+        name: name.into(),
+        samples: vec![vec![1.0, 2.0], vec![]],
+        payload: vec![0; 300],
+        source: Some(Source {
+            host: "localhost".into(),
+        }),
+    }
+}
+
+#[test]
+fn test_read_consecutive_messages() {
+    let mut buf = Vec::new();
+    event("first").encode(&mut buf).unwrap();
+    let first_len = buf.len();
+    event("second").encode(&mut buf).unwrap();
+    buf.extend_from_slice(b"tail");
+
+    let mut reader = Cursor::new(buf);
+    assert_eq!(Event::read_from(&mut reader).unwrap(), event("first"));
+    assert_eq!(reader.position() as usize, first_len);
+
+    let entries = Entries::read_from(&mut reader).unwrap();
+    assert!(matches!(entries.get(1), Some(Value::Str(name)) if name == "second"));
+
+    let mut rest = String::new();
+    reader.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "tail");
+}
+
+#[test]
+fn test_read_truncated_message() {
+    let mut buf = Vec::new();
+    event("first").encode(&mut buf).unwrap();
+
+    for len in [0, 1, buf.len() / 2, buf.len() - 1] {
+        assert!(Entries::read_from(&buf[..len]).is_err());
+    }
+}
+
+#[test]
+fn test_read_huge_length_prefix() {
+    // Bytes field claiming 4 GiB of payload, followed by nothing.
+    let buf = [0x17, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F];
+    assert!(Entries::read_from(&buf[..]).is_err());
+}
+
+#[test]
+fn test_read_overflowing_varints() {
+    let mut overlong = vec![0x15];
+    overlong.extend([0xFF; 10]);
+    overlong.push(0x01);
+    let mut header = vec![0xF5];
+    header.extend([0xFF; 9]);
+    header.push(0x01);
+    for buf in [overlong, header] {
+        let err = Entries::read_from(&buf[..]).unwrap_err();
+        assert_eq!(err.kind(), &cor::ErrorKind::InvalidVarint);
+    }
+}
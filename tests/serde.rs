@@ -0,0 +1,215 @@
+#![cfg(feature = "serde")]
+
+use cor::{Decoder, Encoder, Entries, ErrorKind, entries};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Profile<'a> {
+    name: String,
+    #[serde(borrow)]
+    nick: &'a str,
+    #[serde(rename = "10")]
+    age: u32,
+    #[serde(skip)]
+    cached: u64,
+    scores: Vec<f64>,
+    email: Option<String>,
+}
+
+fn profile() -> Profile<'static> {
+    Profile {
+        name: "name".into(),
+        nick: "nick",
+        age: 42,
+        cached: 0,
+        scores: vec![1.0, 2.5],
+        email: None,
+    }
+}
+
+#[test]
+fn test_struct_keys() {
+    let buf = cor::to_vec(&profile()).unwrap();
+    assert_eq!(
+        Entries::from_slice_exact(&buf).unwrap(),
+        entries! { 0: "name", 1: "nick", 10: 42u, 3: [1.0, 2.5] }
+    );
+
+    let decoded: Profile = cor::from_slice(&buf).unwrap();
+    assert_eq!(decoded, profile());
+    // `&str` fields borrow from the input.
+    let range = buf.as_ptr_range();
+    assert!(range.contains(&decoded.nick.as_ptr()));
+
+    let with_email = Profile {
+        email: Some("a@b".into()),
+        ..profile()
+    };
+    let buf = cor::to_vec(&with_email).unwrap();
+    assert_eq!(cor::from_slice::<Profile>(&buf).unwrap(), with_email);
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Native {
+    #[key = 1]
+    id: u64,
+    #[key = 3]
+    tags: Vec<String>,
+    #[key = 4]
+    payload: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Mirror {
+    #[serde(rename = "1")]
+    id: u64,
+    #[serde(rename = "3")]
+    tags: Vec<String>,
+    #[serde(rename = "4")]
+    payload: Vec<u8>,
+}
+
+#[test]
+fn test_derive_interop() {
+    // Serde writes an empty sequence as an empty list of structs, which the
+    // derived decoder accepts for any `Vec`.
+    let tags = vec!["a".to_string(), "b".to_string()];
+    for (tags, payload) in [(tags, vec![0, 255, 7]), (Vec::new(), Vec::new())] {
+        let mut buf = Vec::new();
+        let native = Native {
+            id: 7,
+            tags: tags.clone(),
+            payload: payload.clone(),
+        };
+        native.encode(&mut buf).unwrap();
+        let mirror: Mirror = cor::from_slice(&buf).unwrap();
+        assert_eq!(
+            mirror,
+            Mirror {
+                id: 7,
+                tags,
+                payload,
+            }
+        );
+        assert_eq!(
+            Native::from_slice_exact(&cor::to_vec(&mirror).unwrap()).unwrap(),
+            native
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    #[serde(rename = "7")]
+    Rect {
+        w: u32,
+        h: u32,
+    },
+    Line(i32, i32),
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Drawing {
+    shapes: Vec<Shape>,
+    origin: (i32, i32),
+    labels: BTreeMap<String, u8>,
+    unit: (),
+}
+
+#[test]
+fn test_data_model() {
+    let drawing = Drawing {
+        shapes: vec![
+            Shape::Circle(1.5),
+            Shape::Rect { w: 2, h: 3 },
+            Shape::Line(-1, 1),
+        ],
+        origin: (-4, 5),
+        labels: BTreeMap::from([("a".into(), 1), ("b".into(), 2)]),
+        unit: (),
+    };
+    let buf = cor::to_vec(&drawing).unwrap();
+    assert_eq!(
+        Entries::from_slice_exact(&buf).unwrap(),
+        entries! {
+            0: [{ 1: 1.5 }, { 7: { 0: 2u, 1: 3u } }, { 3: { 0: -1, 1: 1 } }],
+            1: { 0: -4, 1: 5 },
+            2: [{ 0: "a", 1: 1u }, { 0: "b", 1: 2u }],
+            3: {},
+        }
+    );
+    assert_eq!(cor::from_slice::<Drawing>(&buf).unwrap(), drawing);
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Unit {
+        shape: Shape,
+    }
+    let unit = Unit {
+        shape: Shape::Empty,
+    };
+    let buf = cor::to_vec(&unit).unwrap();
+    assert_eq!(Entries::from_slice_exact(&buf).unwrap(), entries! { 0: 0u });
+    assert_eq!(cor::from_slice::<Unit>(&buf).unwrap(), unit);
+}
+
+#[test]
+fn test_errors() {
+    // Messages must be structs.
+    assert!(cor::to_vec(&5u8).is_err());
+    // Lists must be homogeneous, and `None` needs a field to leave out.
+    #[derive(Serialize)]
+    struct Mixed {
+        items: Vec<Shape>,
+        nested: Vec<Option<u8>>,
+    }
+    let mixed = Mixed {
+        items: vec![Shape::Empty, Shape::Circle(1.0)],
+        nested: vec![],
+    };
+    assert!(cor::to_vec(&mixed).is_err());
+    let mixed = Mixed {
+        items: vec![],
+        nested: vec![None],
+    };
+    assert!(cor::to_vec(&mixed).is_err());
+
+    // A rename must not land on another field's key, even one left out as `None`.
+    #[derive(Serialize)]
+    struct Clash {
+        a: Option<u32>,
+        #[serde(rename = "0")]
+        b: u32,
+    }
+    for a in [Some(1), None] {
+        let err = cor::to_vec(&Clash { a, b: 2 }).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 0 });
+    }
+    #[derive(Serialize)]
+    enum Variant {
+        Clash {
+            a: u32,
+            #[serde(rename = "0")]
+            b: u32,
+        },
+    }
+    let err = cor::to_vec(&(Variant::Clash { a: 1, b: 2 },)).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 0 });
+
+    let buf = cor::to_vec(&Mirror {
+        id: 1,
+        tags: vec!["x".into()],
+        payload: Vec::new(),
+    })
+    .unwrap();
+    #[derive(Deserialize, Debug)]
+    #[allow(dead_code)]
+    struct Wrong {
+        #[serde(rename = "3")]
+        tags: Vec<u64>,
+    }
+    let err = cor::from_slice::<Wrong>(&buf).unwrap_err();
+    assert_eq!(err.path().to_string(), "3.[0]");
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "serde")]
+
+use cor::{Encoder, Entries, List, Value, entries, value};
+use serde::Deserialize;
+use serde_json::json;
+use std::borrow::Cow;
+
+#[derive(Encoder)]
+struct Event {
+    #[key = 1]
+    name: String,
+    #[key = 2]
+    count: u32,
+    #[key = 3]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Summary<'a> {
+    #[serde(rename = "1")]
+    name: &'a str,
+    #[serde(rename = "3")]
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_value_as_deserializer() {
+    let mut buf = Vec::new();
+    Event {
+        name: "boot".into(),
+        count: 3,
+        tags: vec!["a".into()],
+    }
+    .encode(&mut buf)
+    .unwrap();
+
+    let entries = Entries::from_slice_exact(&buf).unwrap();
+    let summary = Summary::deserialize(Value::Struct(entries)).unwrap();
+    assert_eq!(
+        summary,
+        Summary {
+            name: "boot",
+            tags: vec!["a".into()]
+        }
+    );
+
+    let err = u64::deserialize(value!("s")).unwrap_err();
+    assert!(err.to_string().contains("string"), "{err}");
+}
+
+#[test]
+fn test_serialize() {
+    let value = value! { 1: "s", 2: -1, 3: [1u, 2u], 4: { 5: true }, 6: (1 2), 7: #9(3) };
+    assert_eq!(
+        serde_json::to_value(&value).unwrap(),
+        json!({ "1": "s", "2": -1, "3": [1, 2], "4": { "5": true }, "6": [1, 2], "7": { "tag": 9, "data": [3] } })
+    );
+    let Value::Struct(entries) = &value else {
+        unreachable!()
+    };
+    assert_eq!(
+        serde_json::to_string(entries).unwrap(),
+        serde_json::to_string(&value).unwrap()
+    );
+}
+
+#[test]
+fn test_deserialize() {
+    let json = r#"{"1": "s", "2": -1, "3": [1, 2], "4": {"5": true}, "6": [], "7": {"tag": 9, "data": [3]}, "8": 0.5}"#;
+    let value: Value = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        value,
+        value! { 1: "s", 2: -1, 3: [1u, 2u], 4: { 5: true }, 6: []struct, 7: #9(3), 8: 0.5 }
+    );
+    // Strings without escapes are borrowed from the input.
+    assert!(
+        matches!(&value, Value::Struct(e) if matches!(e.get(1), Some(Value::Str(Cow::Borrowed(_)))))
+    );
+
+    // Round trip through a serde format.
+    let original = value! { 1: [[1, -2]], 2: { 3: "x" }, 4: #1(1 2) };
+    let json = serde_json::to_string(&original).unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), original);
+    assert_eq!(
+        serde_json::from_str::<Entries>(r#"{"1": true}"#).unwrap(),
+        entries! { 1: true }
+    );
+
+    // And through the `Value` deserializer itself.
+    assert_eq!(Value::deserialize(original.clone()).unwrap(), original);
+    assert_eq!(
+        Value::deserialize(Value::List(List::Str(vec!["a".into()]))).unwrap(),
+        value!(["a"])
+    );
+}
+
+#[test]
+fn test_deserialize_errors() {
+    assert!(serde_json::from_str::<Value>(r#"[1, "a"]"#).is_err());
+    assert!(serde_json::from_str::<Value>(r#"{"name": 1}"#).is_err());
+    assert!(serde_json::from_str::<Value>(r#"{"70000": 1}"#).is_err());
+    assert!(serde_json::from_str::<Value>(r#"{"tag": 1, "1": 2}"#).is_err());
+    assert!(serde_json::from_str::<Entries>("[1]").is_err());
+}
@@ -0,0 +1,145 @@
+use cor::{Decoder, Encoder};
+
+#[derive(Encoder)]
+struct Full<'a> {
+    #[key = 1]
+    id: u32,
+    #[key = 2]
+    name: &'a str,
+    #[key = 3]
+    scores: Vec<Vec<f32>>,
+    #[key = 4]
+    inner: Inner,
+    #[key = 40]
+    extra: Vec<u64>,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Inner {
+    #[key = 1]
+    flag: bool,
+}
+
+#[derive(Decoder, Debug, PartialEq)]
+struct Partial<'a> {
+    #[key = 2]
+    name: &'a str,
+    #[key = 4]
+    inner: Inner,
+    #[key = 5]
+    missing: Option<u8>,
+    not_on_wire: u8,
+}
+
+fn full_bytes() -> Vec<u8> {
+    let full = Full {
+        id: 7,
+        name: "partial",
+        scores: vec![vec![1.0], vec![2.0, 3.0]],
+        inner: Inner { flag: true },
+        extra: vec![1, 2, 3],
+    };
+    let mut buf = Vec::new();
+    full.encode(&mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn test_skips_unknown_keys() {
+    let buf = full_bytes();
+    let mut reader = &buf[..];
+    let partial = Partial::parse(&mut reader).unwrap();
+    assert!(reader.is_empty());
+    assert_eq!(
+        partial,
+        Partial {
+            name: "partial",
+            inner: Inner { flag: true },
+            missing: None,
+            not_on_wire: 0,
+        }
+    );
+}
+
+#[test]
+fn test_missing_required_field() {
+    #[derive(Decoder, Debug)]
+    struct Required {
+        #[key = 9]
+        _value: u64,
+    }
+    let buf = full_bytes();
+    let err = Required::parse(&mut &buf[..]).unwrap_err();
+    assert!(err.to_string().contains("key `9`"), "{err}");
+}
+
+#[test]
+fn test_invalid_skipped_value() {
+    let mut buf = full_bytes();
+    // Cut the skipped `extra` list short, along with the end marker.
+    buf.truncate(buf.len() - 2);
+    assert!(Partial::parse(&mut &buf[..]).is_err());
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Order {
+    #[key = 1]
+    line: Line,
+    #[key = 2]
+    notes: Vec<String>,
+}
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Line {
+    #[key = 0]
+    sku: String,
+    #[key = 1]
+    counts: Vec<u16>,
+}
+
+#[test]
+fn test_nested_struct() {
+    let order = Order {
+        line: Line {
+            sku: "tea".into(),
+            counts: vec![1, 2],
+        },
+        notes: vec!["hot".into()],
+    };
+    let mut buf = Vec::new();
+    order.encode(&mut buf).unwrap();
+
+    assert_eq!(Order::parse(&mut &buf[..]).unwrap(), order);
+    assert_eq!(Order::read_from(&buf[..]).unwrap(), order);
+}
+
+#[test]
+fn test_nested_conversion_error() {
+    // A line under key 1 whose counts are `[1, 70000]`.
+    let buf = [0x19, 0x18, 0x25, 0x01, 0xF0, 0xA2, 0x04, 0x0A, 0x0A];
+    for err in [
+        Order::parse(&mut &buf[..]).unwrap_err(),
+        Order::read_from(&buf[..]).unwrap_err(),
+    ] {
+        assert!(matches!(err.kind(), cor::ErrorKind::Conversion { .. }));
+        assert_eq!(err.offset(), None);
+        assert_eq!(err.path().to_string(), "1.1.[1]");
+    }
+}
+
+#[test]
+fn test_empty_list_of_any_type() {
+    // Empty lists of strings for the counts and of structs for the notes.
+    let buf = [0x19, 0x06, 0x00, 0x18, 0x06, 0x0A, 0x28, 0x09, 0x0A];
+    let order = Order::parse(&mut &buf[..]).unwrap();
+    assert_eq!(
+        order,
+        Order {
+            line: Line {
+                sku: String::new(),
+                counts: vec![],
+            },
+            notes: vec![],
+        }
+    );
+}
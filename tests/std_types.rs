@@ -0,0 +1,105 @@
+use cor::{Decoder, Encoder, Entries, Value};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::num::{NonZeroI64, NonZeroU32};
+use std::path::PathBuf;
+
+#[derive(Encoder, Decoder, Debug, PartialEq)]
+struct Config {
+    #[key = 1]
+    bind: SocketAddr,
+    #[key = 2]
+    peer: IpAddr,
+    #[key = 3]
+    v4: Ipv4Addr,
+    #[key = 4]
+    v6: Ipv6Addr,
+    #[key = 5]
+    data_dir: PathBuf,
+    #[key = 6]
+    workers: NonZeroU32,
+    #[key = 7]
+    offset: Option<NonZeroI64>,
+}
+
+#[test]
+fn test_std_types() {
+    let config = Config {
+        bind: "[::1]:8080".parse().unwrap(),
+        peer: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        v4: Ipv4Addr::LOCALHOST,
+        v6: Ipv6Addr::UNSPECIFIED,
+        data_dir: PathBuf::from("/var/lib/cor"),
+        workers: NonZeroU32::new(8).unwrap(),
+        offset: NonZeroI64::new(-5),
+    };
+
+    let mut buf = Vec::new();
+    config.encode(&mut buf).unwrap();
+
+    let entries = Entries::parse(&mut &buf[..]).unwrap();
+    assert!(matches!(entries.get(3), Some(Value::Bytes(b)) if b.len() == 4));
+    assert!(matches!(entries.get(5), Some(Value::Str(path)) if path == "/var/lib/cor"));
+    assert_eq!(Config::decode(&entries).unwrap(), config);
+}
+
+#[test]
+fn test_non_zero_rejects_zero() {
+    let mut entries = Entries::new();
+    entries.insert(1, Value::Bytes(vec![127, 0, 0, 1, 0, 80].into()));
+    entries.insert(2, Value::Bytes(vec![127, 0, 0, 1].into()));
+    entries.insert(3, Value::Bytes(vec![127, 0, 0, 1].into()));
+    entries.insert(4, Value::Bytes(vec![0; 16].into()));
+    entries.insert(5, Value::Str(".".into()));
+    entries.insert(6, Value::UInt(0));
+
+    let err = Config::decode(&entries).unwrap_err();
+    assert!(err.to_string().contains("key `6`"));
+}
+
+#[test]
+fn test_ip_invalid_length() {
+    let mut entries = Entries::new();
+    entries.insert(1, Value::Bytes(vec![127, 0, 0].into()));
+    let err = Config::decode(&entries).unwrap_err();
+    assert!(err.to_string().contains("key `1`"));
+}
+
+#[test]
+fn test_ip_addr_invalid_length() {
+    let mut entries = Entries::new();
+    entries.insert(1, Value::Bytes(vec![127, 0, 0, 1, 0, 80].into()));
+    entries.insert(2, Value::Bytes(vec![10, 0, 0].into()));
+    let err = Config::decode(&entries).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("4 or 16 bytes for `core::net::ip_addr::IpAddr`")
+    );
+    assert!(err.to_string().contains("key `2`"));
+}
+
+#[test]
+fn test_socket_addr_v6_scope() {
+    let mut config = Config {
+        bind: SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 7, 3)),
+        peer: IpAddr::V6(Ipv6Addr::LOCALHOST),
+        v4: Ipv4Addr::LOCALHOST,
+        v6: Ipv6Addr::UNSPECIFIED,
+        data_dir: PathBuf::from("/"),
+        workers: NonZeroU32::MIN,
+        offset: None,
+    };
+    let mut buf = Vec::new();
+    config.encode(&mut buf).unwrap();
+    let entries = Entries::parse(&mut &buf[..]).unwrap();
+    assert!(matches!(entries.get(1), Some(Value::Bytes(b)) if b.len() == 26));
+    assert_eq!(Config::decode(&entries).unwrap(), config);
+    assert_eq!(Config::parse(&mut &buf[..]).unwrap(), config);
+
+    // Without a flow label or scope id the address stays 18 bytes long.
+    config.bind = "[::1]:8080".parse().unwrap();
+    let mut buf = Vec::new();
+    config.encode(&mut buf).unwrap();
+    let entries = Entries::parse(&mut &buf[..]).unwrap();
+    assert!(matches!(entries.get(1), Some(Value::Bytes(b)) if b.len() == 18));
+    assert_eq!(Config::parse(&mut &buf[..]).unwrap(), config);
+}
@@ -0,0 +1,138 @@
+use cor::{BytesFormat, Entries, ErrorKind, List, Path, Printer, Segment, Value, entries, value};
+use std::borrow::Cow;
+
+#[test]
+fn test_round_trip() {
+    let values = [
+        value! {
+            1: "name",
+            2: 42u,
+            3: [1.5f, -2.0f],
+            4: { 1: true, 2: -7 },
+            5: (0 127 255),
+            6: #9(1 2),
+            7: [[1u], [true]],
+            8: [{ 1: 0.1 }],
+        },
+        Value::F64(f64::INFINITY),
+        Value::F32(f32::NEG_INFINITY),
+        Value::F64(-0.0),
+        Value::F64(1e-300),
+        Value::F32(f32::MIN_POSITIVE),
+        Value::Int(i64::MIN),
+        Value::UInt(u64::MAX),
+        Value::Str("quote \" slash \\ tab \t nul \0 bell \u{7} é".into()),
+        Value::List(List::Str(Vec::new())),
+        Value::List(List::List(vec![List::F32(Vec::new())])),
+        Value::Struct(Entries::new()),
+    ];
+    for value in values {
+        let text = value.to_text();
+        assert_eq!(Value::from_text(&text).unwrap(), value, "{text}");
+    }
+
+    let nan = Value::F32(f32::NAN).to_text();
+    assert!(matches!(Value::from_text(&nan), Ok(Value::F32(val)) if val.is_nan()));
+}
+
+#[test]
+fn test_notation() {
+    let value = value! { 1: "a\"b", 2: [1u, 2u], 3: 1.0f, 4: (1 2), 5: #3() };
+    assert_eq!(
+        value.to_text(),
+        r#"{1: "a\"b", 2: [1u, 2u], 3: 1.0f, 4: (1 2), 5: #3()}"#
+    );
+    assert_eq!(Value::List(List::UInt(Vec::new())).to_text(), "[]uint");
+    assert_eq!(Value::Str("\u{1b}[0m".into()).to_text(), r#""\u{1b}[0m""#);
+}
+
+#[test]
+fn test_display_parses_back() {
+    let value = value! { 1: (0 1 255), 2: [(), (7)], 3: #5(1 2), 4: #6() };
+    assert_eq!(Value::from_text(&value.to_string()).unwrap(), value);
+    assert_eq!(Value::from_text(&format!("{value:#}")).unwrap(), value);
+
+    let printer = Printer {
+        bytes: BytesFormat::Base64,
+        ..Printer::default()
+    };
+    let text = printer.display(&value).to_string();
+    assert_eq!(Value::from_text(&text).unwrap(), value, "{text}");
+    assert_eq!(
+        Value::from_text(r#"{1: x"0A0b", 2: b64"AQL/", 3: #2( x"ff" )}"#).unwrap(),
+        value! { 1: (10 11), 2: (1 2 255), 3: #2(255) }
+    );
+}
+
+#[test]
+fn test_hand_written() {
+    let text = r#"
+        // Service configuration
+        {
+            1: "api",        // name
+            2: 8080u,
+            3: [ "a", "b", ],
+            4: { 1: 1e3, 2: -inf },
+            5: []struct,
+        }
+    "#;
+    let entries = Entries::from_text(text).unwrap();
+    let mut expected = entries! { 1: "api", 2: 8080u, 3: ["a", "b"], 4: { 1: 1000.0 } };
+    if let Some(Value::Struct(inner)) = expected.get_mut(4) {
+        inner.insert(2, Value::F64(f64::NEG_INFINITY));
+    }
+    expected.insert(5, value!([]struct));
+    assert_eq!(entries, expected);
+
+    // Strings without escapes borrow from the input.
+    let Some(Value::Str(Cow::Borrowed(name))) = entries.get(1) else {
+        panic!("expected a borrowed string");
+    };
+    assert_eq!(*name, "api");
+}
+
+#[test]
+fn test_errors() {
+    let err = Value::from_text("{1: [1, 2u]}").unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Syntax { .. }));
+    assert_eq!(err.offset(), Some(8));
+    assert_eq!(err.path(), &Path::from(vec![Segment::Key(1)]));
+    assert_eq!(
+        err.to_string(),
+        "list elements must all have the same type at byte 8 (field `1`)"
+    );
+
+    let err = Value::from_text("{1: {2: 300x}}").unwrap_err();
+    assert_eq!(err.offset(), Some(8));
+    assert_eq!(err.path().to_string(), "1.2");
+
+    let err = Entries::from_text("{1: 1, 1: 2}").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 1 });
+    assert_eq!(err.offset(), Some(7));
+
+    // A repeated key is found in a large struct too.
+    let fields: Vec<String> = (0..20_000).map(|key| format!("{key}: 1")).collect();
+    let text = format!("{{{}, 19999: 2}}", fields.join(", "));
+    let err = Entries::from_text(&text).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 19999 });
+
+    for text in [
+        "",
+        "{1: 1",
+        "[]",
+        "\"open",
+        "\"\\q\"",
+        "-1u",
+        "(256)",
+        "1 2",
+        "{70000: 1}",
+        "x\"012\"",
+        "x\"zz\"",
+        "b64\"A!\"",
+        "#7(x\"01\"",
+    ] {
+        assert!(Value::from_text(text).is_err(), "{text}");
+    }
+    assert!(Entries::from_text("[1]").is_err());
+    assert!(Value::from_text(&"[".repeat(1000)).is_err());
+}
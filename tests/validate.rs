@@ -0,0 +1,159 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use cor::{Encoder, ErrorKind, skip_value, validate};
+
+struct CountingAlloc;
+
+thread_local! {
+    // Per thread, so tests running in parallel don't count each other's allocations.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.set(ALLOCATIONS.get() + 1);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[derive(Encoder)]
+struct Order {
+    #[key = 1]
+    id: u64,
+    #[key = 2]
+    items: Vec<Item>,
+    #[key = 3]
+    tags: Vec<String>,
+    #[key = 4]
+    flags: Vec<bool>,
+}
+
+#[derive(Encoder)]
+struct Item {
+    #[key = 0]
+    name: String,
+    #[key = 1]
+    price: f64,
+}
+
+fn order() -> Vec<u8> {
+    let mut buf = Vec::new();
+    Order {
+        id: 42,
+        items: vec![
+            Item {
+                name: "tea".into(),
+                price: 2.5,
+            },
+            Item {
+                name: "scone".into(),
+                price: 3.0,
+            },
+        ],
+        tags: vec!["hot".into(), "to go".into()],
+        flags: vec![true, false],
+    }
+    .encode(&mut buf)
+    .unwrap();
+    buf
+}
+
+#[test]
+fn test_validate_without_allocating() {
+    let buf = order();
+    let before = ALLOCATIONS.get();
+    let len = validate(&buf).unwrap();
+    assert_eq!(ALLOCATIONS.get(), before);
+    assert_eq!(len, buf.len());
+}
+
+#[test]
+fn test_validate_stops_at_end_marker() {
+    let mut buf = order();
+    let len = buf.len();
+    buf.extend([0xFF, 0xFF]);
+    assert_eq!(validate(&buf).unwrap(), len);
+}
+
+#[test]
+fn test_validate_rejects_malformed() {
+    let buf = order();
+    let err = validate(&buf[..buf.len() - 1]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Eof);
+
+    let mut buf = order();
+    let pos = buf.windows(5).position(|w| w == b"scone").unwrap();
+    buf[pos] = 0xFF;
+    let err = validate(&buf).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidUtf8);
+    assert_eq!(err.offset(), Some(pos));
+    assert_eq!(err.path().to_string(), "2.[1].0");
+
+    let err = validate(&[0x0C]).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnknownType { code: 12 });
+
+    // An 11-byte varint, and a header whose varint overflows once 15 is added.
+    let mut buf = vec![0x15];
+    buf.extend([0xFF; 10]);
+    buf.push(0x01);
+    let err = validate(&buf).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidVarint);
+    let mut buf = vec![0xF5];
+    buf.extend([0xFF; 9]);
+    buf.push(0x01);
+    let err = validate(&buf).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::InvalidVarint);
+    assert!(cor::Entries::parse(&mut &buf[..]).is_err());
+    assert!(cor::EntriesRef::parse(&mut &buf[..]).is_err());
+}
+
+#[test]
+fn test_validate_rejects_repeated_keys() {
+    // Key 2 twice, at the top level and inside the struct under key 1.
+    for buf in [&[0x21, 0x21, 0x0A][..], &[0x19, 0x21, 0x21, 0x0A, 0x0A]] {
+        let err = validate(buf).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+        let err = cor::Entries::parse(&mut &buf[..]).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 2 });
+    }
+
+    // Keys past the inline ones are still tracked.
+    let mut buf: Vec<u8> = (0..12).map(|key| key << 4 | 1).collect();
+    buf.extend([0xB1, 0x0A]);
+    let err = validate(&buf).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::DuplicateKey { key: 11 });
+    assert_eq!(
+        validate(&buf[..12].iter().chain(&[0x0A]).copied().collect::<Vec<_>>()).unwrap(),
+        13
+    );
+
+    let options = cor::DecodeOptions {
+        duplicate_keys: cor::DuplicateKeys::LastWins,
+        ..Default::default()
+    };
+    assert_eq!(
+        cor::validate_with(&[0x21, 0x21, 0x0A], &options).unwrap(),
+        3
+    );
+}
+
+#[test]
+fn test_skip_value() {
+    // A list of two strings followed by an unrelated byte.
+    let buf = [0x26, 0x01, b'a', 0x02, b'b', b'c', 0x2A];
+    let mut reader = &buf[..];
+    assert_eq!(skip_value(&mut reader, 8).unwrap(), 6);
+    assert_eq!(reader, [0x2A]);
+
+    let mut reader = &[0x05, b'a'][..];
+    let err = skip_value(&mut reader, 6).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::Eof);
+}
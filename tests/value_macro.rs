@@ -0,0 +1,95 @@
+use std::borrow::Cow;
+
+use cor::{Encoder, Entries, List, Value, entries, value};
+
+#[derive(Encoder)]
+struct Profile {
+    #[key = 1]
+    name: String,
+    #[key = 2]
+    age: u32,
+    #[key = 3]
+    scores: Vec<f64>,
+    #[key = 4]
+    settings: Settings,
+}
+
+#[derive(Encoder)]
+struct Settings {
+    #[key = 1]
+    dark_mode: bool,
+}
+
+#[test]
+fn test_matches_decoded_message() {
+    let mut buf = Vec::new();
+    Profile {
+        name: "name".into(),
+        age: 42,
+        scores: vec![1.0, 2.0],
+        settings: Settings { dark_mode: true },
+    }
+    .encode(&mut buf)
+    .unwrap();
+
+    let expected = entries! { 1: "name", 2: 42u, 3: [1.0, 2.0], 4: { 1: true } };
+    assert_eq!(Entries::parse(&mut &buf[..]).unwrap(), expected);
+    assert_eq!(
+        value! { 1: "name", 2: 42u, 3: [1.0, 2.0], 4: { 1: true } },
+        Value::Struct(expected)
+    );
+}
+
+#[test]
+fn test_literals() {
+    assert_eq!(value!(true), Value::Bool(true));
+    assert_eq!(value!(-7), Value::Int(-7));
+    assert_eq!(value!(7u), Value::UInt(7));
+    assert_eq!(value!(1.5), Value::F64(1.5));
+    assert_eq!(value!(-1.5f), Value::F32(-1.5));
+    assert_eq!(value!(2f), Value::F32(2.0));
+    assert_eq!(value!("hi"), Value::Str("hi".into()));
+    assert_eq!(value!((1 2 3)), Value::Bytes(Cow::Borrowed(&[1, 2, 3])));
+    assert_eq!(value!(b"ab"), Value::Bytes(Cow::Borrowed(b"ab")));
+    assert_eq!(
+        value!(#7(1 2)),
+        Value::Ext {
+            tag: 7,
+            data: Cow::Borrowed(&[1, 2])
+        }
+    );
+    assert_eq!(value!(-9223372036854775808), Value::Int(i64::MIN));
+    assert_eq!(value! {}, Value::Struct(Entries::new()));
+}
+
+#[test]
+fn test_lists() {
+    assert_eq!(value!([1u, 2u,]), Value::List(List::UInt(vec![1, 2])));
+    assert_eq!(
+        value!([[1], [true, false]]),
+        Value::List(List::List(vec![
+            List::Int(vec![1]),
+            List::Bool(vec![true, false])
+        ]))
+    );
+    assert_eq!(
+        value!([{ 1: "a" }, {}]),
+        Value::List(List::Struct(vec![entries! { 1: "a" }, Entries::new()]))
+    );
+    assert_eq!(
+        value!([(1), (2 3)]),
+        Value::List(List::Bytes(vec![
+            Cow::Borrowed(&[1]),
+            Cow::Borrowed(&[2, 3])
+        ]))
+    );
+}
+
+#[test]
+fn test_debug_round_trip() {
+    let value = value! { 1: "name", 2: 42u, 3: [1.5f, 2.0f], 4: (1 2), 5: [-1, 2] };
+    assert_eq!(
+        format!("{value:?}"),
+        r#"{1: "name", 2: 42u, 3: [1.5, 2.0], 4: (1 2), 5: [-1, 2]}"#
+    );
+}